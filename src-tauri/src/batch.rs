@@ -0,0 +1,82 @@
+// Output filenames for exporting each clip separately
+
+use crate::types::ExportClip;
+use std::path::Path;
+
+// Characters that aren't allowed in filenames on at least one platform
+const UNSAFE_FILENAME_CHARS: &[char] = &['/', '\\', ':', '*', '?', '"', '<', '>', '|'];
+
+// Extensions ffmpeg can pick a muxer from; anything else gets ".mp4" added
+const CONTAINER_EXTENSIONS: &[&str] = &["mp4", "m4v", "mov", "mkv", "webm", "avi", "mxf", "ts"];
+
+/// Replace characters that can't appear in a filename with '_'
+fn sanitize_filename_part(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| if UNSAFE_FILENAME_CHARS.contains(&c) || c.is_control() { '_' } else { c })
+        .collect()
+}
+
+/// Source time as "HH-MM-SS-mmm", which sorts correctly and is safe in filenames
+fn format_filename_time(ms: u64) -> String {
+    format!(
+        "{:02}-{:02}-{:02}-{:03}",
+        ms / 3_600_000,
+        (ms / 60_000) % 60,
+        (ms / 1000) % 60,
+        ms % 1000
+    )
+}
+
+/// Check a template before any clip is rendered with it
+pub fn validate_filename_template(template: &str) -> Result<(), String> {
+    if template.trim().is_empty() {
+        return Err("Filename template is empty".to_string());
+    }
+    if template.contains(['/', '\\']) {
+        return Err("Filename template can't contain folders".to_string());
+    }
+    
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        let after = &rest[open + 1..];
+        let close = after
+            .find('}')
+            .ok_or_else(|| format!("Unclosed '{{' in filename template \"{}\"", template))?;
+        let name = &after[..close];
+        if !matches!(name, "index" | "asset_name" | "in" | "out") {
+            return Err(format!(
+                "Unknown placeholder {{{}}}; use {{index}}, {{asset_name}}, {{in}} or {{out}}",
+                name
+            ));
+        }
+        rest = &after[close + 1..];
+    }
+    
+    Ok(())
+}
+
+/// Fill in the template for the clip at `index` (0-based) of `count`. {index} is 1-based and
+/// zero-padded so the files sort in timeline order; ".mp4" is added unless the template ends in a container extension.
+pub fn render_filename_template(template: &str, index: usize, count: usize, clip: &ExportClip) -> String {
+    let width = count.to_string().len().max(2);
+    let asset = Path::new(&clip.asset_path);
+    let asset_name = asset.file_stem().unwrap_or(asset.as_os_str()).to_string_lossy();
+    
+    // The asset name goes in last so braces in it are never taken for placeholders
+    let name = template
+        .replace("{index}", &format!("{:0width$}", index + 1, width = width))
+        .replace("{in}", &format_filename_time(clip.in_ms))
+        .replace("{out}", &format_filename_time(clip.out_ms))
+        .replace("{asset_name}", &asset_name);
+    let name = sanitize_filename_part(&name);
+    
+    // Look at the template, not the result, so a dot in an asset name isn't taken for an extension,
+    // and only accept real containers so "{index}.{asset_name}" still gets one
+    let extension = Path::new(template).extension().map(|e| e.to_string_lossy().to_lowercase());
+    if extension.is_some_and(|e| CONTAINER_EXTENSIONS.contains(&e.as_str())) {
+        name
+    } else {
+        format!("{}.mp4", name)
+    }
+}
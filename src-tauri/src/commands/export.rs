@@ -1,7 +1,13 @@
+use crate::batch::*;
+use crate::commands::media::{probe_audio, probe_media, run_ffprobe};
+use crate::export_log::*;
 use crate::ffmpeg::*;
+use crate::metadata::*;
+use crate::playlists::*;
+use crate::subtitles::*;
 use crate::types::*;
 use std::path::PathBuf;
-use tauri::{Emitter, Manager};
+use tauri::{Emitter, Listener, Manager, State};
 
 // Helper function to get app data directory
 fn get_app_data_dir(app: &tauri::AppHandle) -> Result<PathBuf, ErrorEnvelope> {
@@ -14,127 +20,2374 @@ fn get_app_data_dir(app: &tauri::AppHandle) -> Result<PathBuf, ErrorEnvelope> {
         ))
 }
 
+// Silent stereo track used for gaps and sources without audio
+const SILENCE_SOURCE: &str = "anullsrc=channel_layout=stereo:sample_rate=48000";
+
+fn video_encode_args() -> Vec<String> {
+    ["-c:v", "libx264", "-preset", "medium", "-crf", "23", "-pix_fmt", "yuv420p"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect()
+}
+
+fn audio_encode_args() -> Vec<String> {
+    ["-c:a", "aac", "-b:a", "192k", "-ar", "48000", "-ac", "2"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect()
+}
+
+// Re-encode to H.264/AAC with fixed audio layout so segments concat without re-encoding
+fn segment_encode_args() -> Vec<String> {
+    let mut args = video_encode_args();
+    args.extend(audio_encode_args());
+    args
+}
+
+// Keyframe interval for web-optimized exports
+const WEB_GOP_SECONDS: f64 = 2.0;
+
+// Closed, fixed-length GOPs at a constant frame rate. Segments encoded with these share one
+// timebase and start on a keyframe, so the concat demuxer can copy them without timestamp jumps.
+fn web_video_args(fps: f64) -> Vec<String> {
+    let gop = (fps * WEB_GOP_SECONDS).round().max(1.0) as u32;
+    vec![
+        "-r".to_string(),
+        fps.to_string(),
+        "-g".to_string(),
+        gop.to_string(),
+        "-keyint_min".to_string(),
+        gop.to_string(),
+        "-sc_threshold".to_string(),
+        "0".to_string(),
+        "-flags".to_string(),
+        "+cgop".to_string(),
+        "-video_track_timescale".to_string(),
+        "90000".to_string(),
+    ]
+}
+
+// Container flags for a web-optimized file: the moov atom goes first so playback starts
+// before the download finishes, and timestamps start at zero
+fn web_output_args(output_path: &str) -> Vec<String> {
+    let mut args = vec!["-avoid_negative_ts".to_string(), "make_zero".to_string()];
+    if is_mov_family(output_path) {
+        args.extend(["-movflags".to_string(), "+faststart".to_string()]);
+    }
+    args
+}
+
+fn is_mov_family(path: &str) -> bool {
+    let lower = path.to_lowercase();
+    [".mp4", ".m4v", ".mov"].iter().any(|ext| lower.ends_with(ext))
+}
+
+// Run ffmpeg to completion and log it. Returns whether it succeeded, and its stderr.
+async fn run_logged_ffmpeg(args: &[String], label: &str, log: &JobLog) -> Result<(bool, String), ErrorEnvelope> {
+    let ffmpeg = get_ffmpeg_path();
+    let started = std::time::Instant::now();
+    let output = tokio::process::Command::new(&ffmpeg)
+        .args(args)
+        .output()
+        .await;
+    
+    let program = ffmpeg.to_string_lossy();
+    let output = match output {
+        Ok(output) => output,
+        Err(e) => {
+            log.command(label, &program, args, None, started.elapsed(), &e.to_string()).await;
+            return Err(ErrorEnvelope::new(
+                "FFMPEG_ERROR",
+                &format!("Failed to run ffmpeg: {}", e),
+                "Make sure FFmpeg is installed: brew install ffmpeg"
+            ));
+        }
+    };
+    
+    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+    log.command(label, &program, args, output.status.code(), started.elapsed(), &stderr).await;
+    
+    Ok((output.status.success(), stderr))
+}
+
+// Run one segment-producing ffmpeg invocation, mapping failures to SEGMENT_FAILED
+async fn run_segment_ffmpeg(args: &[String], label: &str, log: &JobLog) -> Result<(), ErrorEnvelope> {
+    let (success, stderr) = run_logged_ffmpeg(args, label, log).await?;
+    
+    if !success {
+        return Err(ErrorEnvelope::new(
+            "SEGMENT_FAILED",
+            &format!("Failed to create {}: {}", label, stderr),
+            "Check if the source file is valid"
+        ));
+    }
+    
+    Ok(())
+}
+
+// Speed, in/out and overlap checks for main-track clips, shared with the interchange export
+pub fn clip_timing_problems(clips: &[ExportClip]) -> Vec<String> {
+    let mut problems = Vec::new();
+    let mut previous_end_ms = 0u64;
+    
+    for (i, clip) in clips.iter().enumerate() {
+        if clip.start_ms < previous_end_ms {
+            problems.push(format!(
+                "Clip {}: starts at {}ms, overlapping the previous clip which ends at {}ms",
+                i, clip.start_ms, previous_end_ms
+            ));
+        }
+        
+        // The timeline duration divides by the speed, so it means nothing until the speed is valid
+        if !(0.25..=4.0).contains(&clip.speed) {
+            problems.push(format!("Clip {}: speed {} is outside 0.25x to 4x", i, clip.speed));
+            continue;
+        }
+        previous_end_ms = clip.start_ms.saturating_add(clip.duration_ms());
+        
+        if let Some(freeze_ms) = clip.freeze_ms {
+            if freeze_ms == 0 {
+                problems.push(format!("Clip {}: freeze frame duration must be greater than zero", i));
+            }
+        } else if clip.out_ms <= clip.in_ms {
+            problems.push(format!(
+                "Clip {}: out point ({}ms) must be after in point ({}ms)",
+                i, clip.out_ms, clip.in_ms
+            ));
+        }
+    }
+    
+    problems
+}
+
+// Transitions overlap two directly adjacent clips, so both must be long enough to cover them
+fn transition_problems(request: &ExportRequest) -> Vec<String> {
+    let clips = &request.clips;
+    let mut problems = Vec::new();
+    let mut overlap_ms = vec![0u64; clips.len()];
+    
+    for (i, transition) in request.transitions.iter().enumerate() {
+        let before = transition.after_clip;
+        let after = before.saturating_add(1);
+        
+        if after >= clips.len() {
+            problems.push(format!("Transition {}: there is no clip after clip {}", i, before));
+            continue;
+        }
+        if request.transitions[..i].iter().any(|t| t.after_clip == before) {
+            problems.push(format!("Transition {}: clips {} and {} already have a transition", i, before, after));
+            continue;
+        }
+        if transition.duration_ms == 0 {
+            problems.push(format!("Transition {}: duration must be greater than zero", i));
+            continue;
+        }
+        
+        // A clip with a bad speed has a meaningless, possibly huge duration; its problem is reported already
+        let before_end_ms = clips[before].start_ms.saturating_add(clips[before].duration_ms());
+        if clips[after].start_ms != before_end_ms {
+            problems.push(format!(
+                "Transition {}: clips {} and {} must be adjacent, but there is a gap between them",
+                i, before, after
+            ));
+            continue;
+        }
+        
+        overlap_ms[before] = overlap_ms[before].saturating_add(transition.duration_ms);
+        overlap_ms[after] = overlap_ms[after].saturating_add(transition.duration_ms);
+    }
+    
+    for (i, clip) in clips.iter().enumerate() {
+        let duration_ms = clip.duration_ms();
+        if overlap_ms[i] > duration_ms {
+            problems.push(format!(
+                "Clip {}: transitions need {}ms but the clip is only {}ms long",
+                i, overlap_ms[i], duration_ms
+            ));
+        }
+    }
+    
+    problems
+}
+
+// Out-of-range color settings and missing LUT files
+fn color_problems(clip: &ExportClip) -> Vec<String> {
+    let mut problems = Vec::new();
+    
+    if let Some(color) = &clip.color {
+        if !(-1.0..=1.0).contains(&color.brightness) {
+            problems.push("brightness must be between -1 and 1".to_string());
+        }
+        if !(0.0..=4.0).contains(&color.contrast) {
+            problems.push("contrast must be between 0 and 4".to_string());
+        }
+        if !(0.0..=3.0).contains(&color.saturation) {
+            problems.push("saturation must be between 0 and 3".to_string());
+        }
+        if !(0.1..=10.0).contains(&color.gamma) {
+            problems.push("gamma must be between 0.1 and 10".to_string());
+        }
+        if let Some(temperature) = color.temperature {
+            if !(1000.0..=40000.0).contains(&temperature) {
+                problems.push("white balance must be between 1000K and 40000K".to_string());
+            }
+        }
+    }
+    
+    if let Some(lut_path) = &clip.lut_path {
+        if !lut_path.to_lowercase().ends_with(".cube") {
+            problems.push(format!("LUT must be a .cube file: {}", lut_path));
+        } else if !std::path::Path::new(lut_path).exists() {
+            problems.push(format!("LUT file not found: {}", lut_path));
+        }
+    }
+    
+    problems
+}
+
+// Rotations the transform can apply and crops that fit inside the source
+fn transform_problems(clip: &ExportClip, source: &MediaMetadata) -> Vec<String> {
+    let mut problems = Vec::new();
+    
+    if !clip.rotation.is_multiple_of(90) {
+        problems.push("rotation must be a multiple of 90 degrees".to_string());
+    }
+    
+    if let Some(crop) = &clip.crop {
+        if crop.width == 0
+            || crop.height == 0
+            || crop.x.checked_add(crop.width).is_none_or(|right| right > source.width)
+            || crop.y.checked_add(crop.height).is_none_or(|bottom| bottom > source.height)
+        {
+            problems.push(format!(
+                "crop {}x{} at {},{} doesn't fit the {}x{} source",
+                crop.width, crop.height, crop.x, crop.y, source.width, source.height
+            ));
+        }
+        // yuv420p stores chroma at half resolution, so odd sizes can't be encoded
+        if crop.width % 2 != 0 || crop.height % 2 != 0 {
+            problems.push(format!("crop size {}x{} must be even in both directions", crop.width, crop.height));
+        }
+    }
+    
+    problems
+}
+
+// Check every clip against its probed source before any encoding starts.
+// Collects all problems so the user can fix the whole timeline in one pass.
+async fn validate_export_request(request: &ExportRequest) -> Result<Vec<MediaMetadata>, ErrorEnvelope> {
+    if request.clips.is_empty() {
+        return Err(ErrorEnvelope::new(
+            "EMPTY_TIMELINE",
+            "There are no clips to export",
+            "Add at least one clip to the timeline before exporting"
+        ));
+    }
+    
+    let mut sources = Vec::new();
+    let mut problems = Vec::new();
+    
+    if let Some(color) = &request.gap_color {
+        if !is_valid_color(color) {
+            problems.push(format!("Gap color '{}' is not a valid color", color));
+        }
+    }
+    
+    problems.extend(clip_timing_problems(&request.clips));
+    
+    for (i, clip) in request.clips.iter().enumerate() {
+        let duration_ms = clip.duration_ms();
+        if clip.video_fade_in_ms.saturating_add(clip.video_fade_out_ms) > duration_ms
+            || clip.audio_fade_in_ms.saturating_add(clip.audio_fade_out_ms) > duration_ms
+        {
+            problems.push(format!("Clip {}: fades are longer than the clip ({}ms)", i, duration_ms));
+        }
+        
+        problems.extend(color_problems(clip).into_iter().map(|p| format!("Clip {}: {}", i, p)));
+        
+        // probe_media also reports missing files
+        match probe_media(clip.asset_path.clone()).await {
+            Ok(metadata) => {
+                problems.extend(transform_problems(clip, &metadata).into_iter().map(|p| format!("Clip {}: {}", i, p)));
+                
+                if clip.freeze_ms.is_some() && clip.in_ms >= metadata.duration_ms {
+                    problems.push(format!(
+                        "Clip {}: freeze frame at {}ms is past the end of {} ({}ms)",
+                        i, clip.in_ms, clip.asset_path, metadata.duration_ms
+                    ));
+                } else if clip.freeze_ms.is_none() && clip.out_ms > metadata.duration_ms {
+                    problems.push(format!(
+                        "Clip {}: out point ({}ms) is past the end of {} ({}ms)",
+                        i, clip.out_ms, clip.asset_path, metadata.duration_ms
+                    ));
+                }
+                sources.push(metadata);
+            }
+            Err(e) => problems.push(format!("Clip {}: {}", i, e.message)),
+        }
+    }
+    
+    problems.extend(transition_problems(request));
+    
+    for (t, track) in request.video_tracks.iter().enumerate() {
+        for (i, clip) in track.clips.iter().enumerate() {
+            let name = format!("Track {} clip {}", t + 1, i);
+            
+            if clip.out_ms <= clip.in_ms {
+                problems.push(format!(
+                    "{}: out point ({}ms) must be after in point ({}ms)",
+                    name, clip.out_ms, clip.in_ms
+                ));
+            }
+            if clip.scale <= 0.0 {
+                problems.push(format!("{}: scale must be greater than zero", name));
+            }
+            if !(0.0..=1.0).contains(&clip.opacity) {
+                problems.push(format!("{}: opacity must be between 0 and 1", name));
+            }
+            
+            match probe_media(clip.asset_path.clone()).await {
+                Ok(metadata) if clip.out_ms > metadata.duration_ms => problems.push(format!(
+                    "{}: out point ({}ms) is past the end of {} ({}ms)",
+                    name, clip.out_ms, clip.asset_path, metadata.duration_ms
+                )),
+                Ok(_) => {}
+                Err(e) => problems.push(format!("{}: {}", name, e.message)),
+            }
+        }
+    }
+    
+    for (t, track) in request.audio_tracks.iter().enumerate() {
+        // The ranges sidechaincompress accepts
+        if let Some(ducking) = &track.ducking {
+            if !(0.000976563..=1.0).contains(&ducking.threshold) || !(1.0..=20.0).contains(&ducking.ratio) {
+                problems.push(format!(
+                    "Audio track {}: ducking needs a threshold between 0.001 and 1 and a ratio between 1 and 20",
+                    t + 1
+                ));
+            }
+            if !(0.01..=2000.0).contains(&ducking.attack_ms) || !(0.01..=9000.0).contains(&ducking.release_ms) {
+                problems.push(format!(
+                    "Audio track {}: ducking attack must be 0.01 to 2000ms and release 0.01 to 9000ms",
+                    t + 1
+                ));
+            }
+        }
+        
+        for (i, clip) in track.clips.iter().enumerate() {
+            let name = format!("Audio track {} clip {}", t + 1, i);
+            
+            if clip.out_ms <= clip.in_ms {
+                problems.push(format!(
+                    "{}: out point ({}ms) must be after in point ({}ms)",
+                    name, clip.out_ms, clip.in_ms
+                ));
+            }
+            
+            match probe_audio(&clip.asset_path).await {
+                Ok(duration_ms) if clip.out_ms > duration_ms => problems.push(format!(
+                    "{}: out point ({}ms) is past the end of {} ({}ms)",
+                    name, clip.out_ms, clip.asset_path, duration_ms
+                )),
+                Ok(_) => {}
+                Err(e) => problems.push(format!("{}: {}", name, e.message)),
+            }
+        }
+    }
+    
+    for (i, overlay) in request.text_overlays.iter().enumerate() {
+        let name = format!("Text {}", i + 1);
+        
+        if overlay.text.trim().is_empty() {
+            problems.push(format!("{}: text is empty", name));
+        }
+        if overlay.end_ms <= overlay.start_ms {
+            problems.push(format!("{}: must end after it starts", name));
+        } else if overlay.fade_in_ms.saturating_add(overlay.fade_out_ms) > overlay.end_ms - overlay.start_ms {
+            problems.push(format!("{}: fades are longer than the text is shown", name));
+        }
+        if !std::path::Path::new(&overlay.font_file).exists() {
+            problems.push(format!("{}: font file not found: {}", name, overlay.font_file));
+        }
+        if overlay.font_size == 0 {
+            problems.push(format!("{}: font size must be greater than zero", name));
+        }
+        
+        let colors = std::iter::once(&overlay.color).chain(overlay.box_color.as_ref());
+        for color in colors.filter(|c| !is_valid_color(c)) {
+            problems.push(format!("{}: '{}' is not a valid color", name, color));
+        }
+    }
+    
+    if let Some(watermark) = &request.watermark {
+        if !std::path::Path::new(&watermark.image_path).exists() {
+            problems.push(format!("Watermark image not found: {}", watermark.image_path));
+        }
+        if watermark.scale <= 0.0 || watermark.scale > 1.0 {
+            problems.push("Watermark scale must be greater than 0 and at most 1".to_string());
+        }
+        if !(0.0..=1.0).contains(&watermark.opacity) {
+            problems.push("Watermark opacity must be between 0 and 1".to_string());
+        }
+        if let (Some(start_ms), Some(end_ms)) = (watermark.start_ms, watermark.end_ms) {
+            if end_ms <= start_ms {
+                problems.push("Watermark must end after it starts".to_string());
+            }
+        }
+    }
+    
+    if let Some(loudness) = &request.loudness {
+        if !(-70.0..=-5.0).contains(&loudness.integrated_lufs)
+            || !(-9.0..=0.0).contains(&loudness.true_peak_db)
+            || !(1.0..=50.0).contains(&loudness.lra)
+        {
+            problems.push(
+                "Loudness target must be -70 to -5 LUFS, true peak -9 to 0 dBTP and LRA 1 to 50".to_string()
+            );
+        }
+    }
+    
+    for (i, track) in request.subtitles.iter().enumerate() {
+        let name = format!("Subtitles {}", i + 1);
+        
+        if let Err(e) = read_subtitle_file(&track.path).await {
+            problems.push(format!("{}: {}", name, e.message));
+        }
+        if let Some(font_name) = &track.style.font_name {
+            if font_name.contains([',', '=', '\'', ':']) {
+                problems.push(format!("{}: font name '{}' contains unsupported characters", name, font_name));
+            }
+        }
+        if let Some(language) = &track.language {
+            if !language.chars().all(|c| c.is_ascii_alphabetic()) {
+                problems.push(format!("{}: language '{}' should be a code like \"eng\"", name, language));
+            }
+        }
+    }
+    
+    if let Some(target) = &request.target_size {
+        if target.size_bytes == 0 {
+            problems.push("Target size must be greater than zero".to_string());
+        }
+        if !(32..=512).contains(&target.audio_bitrate_kbps) {
+            problems.push("Target size audio bitrate must be between 32 and 512 kbps".to_string());
+        }
+    }
+    
+    if let Some(streaming) = &request.streaming {
+        if streaming.renditions.is_empty() {
+            problems.push("Streaming output needs at least one rendition".to_string());
+        }
+        if !(1..=30).contains(&streaming.segment_duration_s) {
+            problems.push("Streaming segment duration must be between 1 and 30 seconds".to_string());
+        }
+        for (i, rendition) in streaming.renditions.iter().enumerate() {
+            if rendition.height == 0 || rendition.height % 2 != 0 {
+                problems.push(format!("Rendition {}: height must be a positive even number", i));
+            }
+            if rendition.video_bitrate_kbps == 0 || rendition.audio_bitrate_kbps == 0 {
+                problems.push(format!("Rendition {}: bitrates must be greater than zero", i));
+            }
+            if streaming.renditions[..i].iter().any(|r| r.height == rendition.height) {
+                problems.push(format!("Rendition {}: there is already a {}p rendition", i, rendition.height));
+            }
+        }
+        if request.target_size.is_some() {
+            problems.push("Streaming output can't be combined with a target file size".to_string());
+        }
+        if request.subtitles.iter().any(|s| s.mode == SubtitleMode::Soft) {
+            problems.push("Streaming output can only burn in subtitles".to_string());
+        }
+        if has_container_metadata(request) {
+            problems.push("Streaming output can't carry container metadata or chapters".to_string());
+        }
+        if request.web_optimized {
+            problems.push("Web optimization applies to single-file output, not streaming".to_string());
+        }
+        if request.poster_frame.is_some() || request.contact_sheet.is_some() {
+            problems.push("Poster frames and contact sheets need single-file output, not streaming".to_string());
+        }
+    }
+    
+    if let Some(metadata) = &request.metadata {
+        if let Some(creation_time) = &metadata.creation_time {
+            if !is_iso_datetime(creation_time) {
+                problems.push(format!("Creation time '{}' should look like \"2024-05-01T12:30:00Z\"", creation_time));
+            }
+        }
+        if let Some(language) = &metadata.language {
+            if language.len() != 3 || !language.chars().all(|c| c.is_ascii_alphabetic()) {
+                problems.push(format!("Metadata language '{}' should be a code like \"eng\"", language));
+            }
+        }
+    }
+    
+    if let Some(poster) = &request.poster_frame {
+        let (start_ms, end_ms) = match request.range {
+            Some(range) => (range.start_ms, range.end_ms),
+            None => (0, request.clips.iter().map(|c| c.start_ms.saturating_add(c.duration_ms())).max().unwrap_or(0)),
+        };
+        if !(start_ms..end_ms).contains(&poster.time_ms) {
+            problems.push(format!(
+                "Poster frame at {}ms is outside the exported {}ms to {}ms",
+                poster.time_ms, start_ms, end_ms
+            ));
+        }
+    }
+    
+    if let Some(sheet) = &request.contact_sheet {
+        if !(1..=10).contains(&sheet.columns) || !(1..=10).contains(&sheet.rows) {
+            problems.push("Contact sheet must have 1 to 10 columns and rows".to_string());
+        }
+        if !(64..=960).contains(&sheet.thumbnail_width) || sheet.thumbnail_width % 2 != 0 {
+            problems.push("Contact sheet thumbnail width must be an even number from 64 to 960".to_string());
+        }
+        if let Some(font_file) = &sheet.font_file {
+            if !std::path::Path::new(font_file).exists() {
+                problems.push(format!("Contact sheet font file not found: {}", font_file));
+            }
+        }
+    }
+    
+    if !request.chapters.is_empty() && request.chapters_per_clip {
+        problems.push("Use either chapter markers or one chapter per clip, not both".to_string());
+    }
+    for (i, chapter) in request.chapters.iter().enumerate() {
+        if chapter.title.trim().is_empty() {
+            problems.push(format!("Chapter {} needs a title", i + 1));
+        }
+    }
+    
+    if !problems.is_empty() {
+        return Err(ErrorEnvelope::new(
+            "INVALID_CLIPS",
+            &format!("{} problem(s) found in the timeline:\n{}", problems.len(), problems.join("\n")),
+            "Fix the listed clips and try exporting again"
+        ));
+    }
+    
+    Ok(sources)
+}
+
+// Frame size a clip comes out at after its crop and rotation
+fn transformed_size(clip: &ExportClip, source: &MediaMetadata) -> (u32, u32) {
+    let (width, height) = clip
+        .crop
+        .map_or((source.width, source.height), |c| (c.width, c.height));
+    if clip.rotation % 180 == 90 {
+        (height, width)
+    } else {
+        (width, height)
+    }
+}
+
+// Headroom on top of size estimates; re-encoding can come out larger than the source
+const SPACE_MARGIN: f64 = 1.5;
+
+// Fail early when `dir`'s volume can't hold `required_bytes`. Volumes that don't report
+// free space (some network shares) are let through.
+fn check_free_space(dir: &std::path::Path, required_bytes: u64, what: &str) -> Result<(), ErrorEnvelope> {
+    let Ok(available) = fs2::available_space(dir) else {
+        return Ok(());
+    };
+    
+    if available < required_bytes {
+        return Err(ErrorEnvelope::new(
+            "INSUFFICIENT_DISK_SPACE",
+            &format!(
+                "{} needs about {} MB in {} but only {} MB is free",
+                what,
+                required_bytes / 1_000_000,
+                dir.display(),
+                available / 1_000_000
+            ),
+            "Free up disk space or choose another location"
+        ));
+    }
+    
+    Ok(())
+}
+
+// Segments take about as much space as the parts of the sources they cover
+fn estimate_segment_bytes(request: &ExportRequest, sources: &[MediaMetadata]) -> u64 {
+    let bytes: f64 = request
+        .clips
+        .iter()
+        .zip(sources)
+        .map(|(clip, source)| {
+            let source_kbps = source.size_bytes.unwrap_or(0) as f64 * 8.0 / source.duration_ms.max(1) as f64;
+            source_kbps * clip.duration_ms() as f64 / 8.0
+        })
+        .sum();
+    (bytes * SPACE_MARGIN) as u64
+}
+
+// The output has the bitrate of the segments it's made from, or the size it was asked to fit
+async fn estimate_output_bytes(job: Option<&ExportJob>, list_file: &str, total_duration_ms: u64) -> u64 {
+    if let Some(target) = job.and_then(|j| j.request.target_size.as_ref()) {
+        return target.size_bytes;
+    }
+    if let Some(streaming) = job.and_then(|j| j.request.streaming.as_ref()) {
+        let kbps: u64 = streaming
+            .renditions
+            .iter()
+            .map(|r| (r.video_bitrate_kbps + r.audio_bitrate_kbps) as u64)
+            .sum();
+        return (kbps as f64 * total_duration_ms as f64 / 8.0 * SPACE_MARGIN) as u64;
+    }
+    
+    let segment_paths: Vec<String> = match job {
+        Some(job) => job.segments.iter().map(|s| s.path.clone()).collect(),
+        None => tokio::fs::read_to_string(list_file)
+            .await
+            .unwrap_or_default()
+            .lines()
+            .filter_map(|line| line.strip_prefix("file '")?.strip_suffix('\'').map(str::to_string))
+            .collect(),
+    };
+    
+    let mut bytes = 0;
+    for path in segment_paths {
+        bytes += tokio::fs::metadata(&path).await.map(|m| m.len()).unwrap_or(0);
+    }
+    (bytes as f64 * SPACE_MARGIN) as u64
+}
+
+// Where the output (a file, or a directory for streaming) is rendered before it replaces `output_path`.
+// Same directory so the final rename stays on one volume; the extension is kept so ffmpeg picks the same container.
+fn temp_output_path(output_path: &str) -> String {
+    let path = std::path::Path::new(output_path);
+    let stem = path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    let name = match path.extension() {
+        Some(ext) => format!(".{}.partial.{}", stem, ext.to_string_lossy()),
+        None => format!(".{}.partial", stem),
+    };
+    path.with_file_name(name).to_string_lossy().to_string()
+}
+
+// How much of a span starting at `start_ms` falls outside the range at its head and tail,
+// or None when it doesn't intersect the range at all
+fn range_cuts(range: &TimelineRange, start_ms: u64, duration_ms: u64) -> Option<(u64, u64)> {
+    let end_ms = start_ms.saturating_add(duration_ms);
+    if end_ms <= range.start_ms || start_ms >= range.end_ms {
+        return None;
+    }
+    Some((range.start_ms.saturating_sub(start_ms), end_ms.saturating_sub(range.end_ms)))
+}
+
+// Cut a clip down to the range and move it to the range's time base, or None if nothing of it is left.
+// Fades only survive on ends the range didn't cut.
+fn trim_clip_to_range(clip: &ExportClip, range: &TimelineRange) -> Option<ExportClip> {
+    let duration_ms = clip.duration_ms();
+    let (head_ms, tail_ms) = range_cuts(range, clip.start_ms, duration_ms)?;
+    let mut trimmed = clip.clone();
+    
+    if let Some(freeze_ms) = clip.freeze_ms {
+        trimmed.freeze_ms = Some(freeze_ms - head_ms - tail_ms);
+    } else {
+        // Timeline time runs at `speed` through the source, and backwards when reversed
+        let head_source_ms = (head_ms as f64 * clip.speed).round() as u64;
+        let tail_source_ms = (tail_ms as f64 * clip.speed).round() as u64;
+        if clip.reverse {
+            trimmed.in_ms += tail_source_ms;
+            trimmed.out_ms = trimmed.out_ms.saturating_sub(head_source_ms);
+        } else {
+            trimmed.in_ms += head_source_ms;
+            trimmed.out_ms = trimmed.out_ms.saturating_sub(tail_source_ms);
+        }
+        
+        // Rounding to source time can use up a sliver of a clip the range only just touches
+        if trimmed.out_ms <= trimmed.in_ms {
+            return None;
+        }
+    }
+    
+    trimmed.start_ms = clip.start_ms.max(range.start_ms) - range.start_ms;
+    
+    let trimmed_ms = trimmed.duration_ms();
+    if head_ms > 0 {
+        trimmed.video_fade_in_ms = 0;
+        trimmed.audio_fade_in_ms = 0;
+    }
+    if tail_ms > 0 {
+        trimmed.video_fade_out_ms = 0;
+        trimmed.audio_fade_out_ms = 0;
+    }
+    
+    // The kept fades still have to fit the shorter clip
+    trimmed.video_fade_in_ms = trimmed.video_fade_in_ms.min(trimmed_ms);
+    trimmed.video_fade_out_ms = trimmed.video_fade_out_ms.min(trimmed_ms - trimmed.video_fade_in_ms);
+    trimmed.audio_fade_in_ms = trimmed.audio_fade_in_ms.min(trimmed_ms);
+    trimmed.audio_fade_out_ms = trimmed.audio_fade_out_ms.min(trimmed_ms - trimmed.audio_fade_in_ms);
+    
+    Some(trimmed)
+}
+
+// Reduce a validated request to its range: clips, tracks, overlays and the watermark are trimmed and
+// rebased so the range starts at 0, and `sources` keeps the probes of the clips that remain.
+// Subtitles are shifted when they are retimed.
+fn apply_range(
+    request: ExportRequest,
+    sources: Vec<MediaMetadata>,
+) -> Result<(ExportRequest, Vec<MediaMetadata>), ErrorEnvelope> {
+    let Some(range) = request.range else {
+        return Ok((request, sources));
+    };
+    
+    if range.end_ms <= range.start_ms {
+        return Err(ErrorEnvelope::new(
+            "INVALID_RANGE",
+            &format!("Range end ({}ms) must be after its start ({}ms)", range.end_ms, range.start_ms),
+            "Choose a range with an out point after the in point"
+        ));
+    }
+    
+    // Keep track of where each surviving clip ended up so transitions can follow it
+    let mut clips = Vec::new();
+    let mut kept_sources = Vec::new();
+    let mut new_index = vec![None; request.clips.len()];
+    for (i, (clip, source)) in request.clips.iter().zip(sources).enumerate() {
+        if let Some(trimmed) = trim_clip_to_range(clip, &range) {
+            new_index[i] = Some(clips.len());
+            clips.push(trimmed);
+            kept_sources.push(source);
+        }
+    }
+    
+    if clips.is_empty() {
+        return Err(ErrorEnvelope::new(
+            "INVALID_RANGE",
+            &format!("There are no clips between {}ms and {}ms", range.start_ms, range.end_ms),
+            "Choose a range that covers at least one clip"
+        ));
+    }
+    
+    // A transition survives only if both its clips do and are still long enough to overlap
+    let transitions = request
+        .transitions
+        .iter()
+        .filter_map(|t| {
+            let before = new_index[t.after_clip]?;
+            let after = new_index[t.after_clip + 1]?;
+            let fits = clips[before].duration_ms() >= t.duration_ms && clips[after].duration_ms() >= t.duration_ms;
+            fits.then(|| ExportTransition {
+                after_clip: before,
+                ..t.clone()
+            })
+        })
+        .collect();
+    
+    let video_tracks = request
+        .video_tracks
+        .iter()
+        .map(|track| ExportVideoTrack {
+            clips: track
+                .clips
+                .iter()
+                .filter_map(|clip| {
+                    let (head_ms, tail_ms) = range_cuts(&range, clip.start_ms, clip.out_ms.saturating_sub(clip.in_ms))?;
+                    Some(TrackClip {
+                        in_ms: clip.in_ms + head_ms,
+                        out_ms: clip.out_ms.saturating_sub(tail_ms),
+                        start_ms: clip.start_ms.max(range.start_ms) - range.start_ms,
+                        ..clip.clone()
+                    })
+                })
+                .collect(),
+        })
+        .collect();
+    
+    let audio_tracks = request
+        .audio_tracks
+        .iter()
+        .map(|track| ExportAudioTrack {
+            clips: track
+                .clips
+                .iter()
+                .filter_map(|clip| {
+                    let (head_ms, tail_ms) = range_cuts(&range, clip.start_ms, clip.out_ms.saturating_sub(clip.in_ms))?;
+                    Some(AudioTrackClip {
+                        in_ms: clip.in_ms + head_ms,
+                        out_ms: clip.out_ms.saturating_sub(tail_ms),
+                        start_ms: clip.start_ms.max(range.start_ms) - range.start_ms,
+                        ..clip.clone()
+                    })
+                })
+                .collect(),
+            ..track.clone()
+        })
+        .collect();
+    
+    let text_overlays = request
+        .text_overlays
+        .iter()
+        .filter_map(|overlay| {
+            let (head_ms, tail_ms) = range_cuts(&range, overlay.start_ms, overlay.end_ms.saturating_sub(overlay.start_ms))?;
+            Some(TextOverlay {
+                start_ms: overlay.start_ms.max(range.start_ms) - range.start_ms,
+                end_ms: overlay.end_ms.min(range.end_ms) - range.start_ms,
+                fade_in_ms: if head_ms > 0 { 0 } else { overlay.fade_in_ms },
+                fade_out_ms: if tail_ms > 0 { 0 } else { overlay.fade_out_ms },
+                ..overlay.clone()
+            })
+        })
+        .collect();
+    
+    // A watermark window outside the range is dropped; one crossing its edges is clipped to them
+    let length_ms = range.end_ms - range.start_ms;
+    let watermark = request.watermark.clone().and_then(|watermark| {
+        let shown_from = watermark.start_ms.unwrap_or(0);
+        let shown_until = watermark.end_ms.unwrap_or(u64::MAX);
+        if shown_until <= range.start_ms || shown_from >= range.end_ms {
+            return None;
+        }
+        Some(Watermark {
+            start_ms: watermark.start_ms.map(|ms| ms.saturating_sub(range.start_ms).min(length_ms)),
+            end_ms: watermark.end_ms.map(|ms| ms.saturating_sub(range.start_ms).min(length_ms)),
+            ..watermark
+        })
+    });
+    
+    let request = ExportRequest {
+        clips,
+        transitions,
+        video_tracks,
+        audio_tracks,
+        text_overlays,
+        watermark,
+        ..request
+    };
+    Ok((request, kept_sources))
+}
+
+fn export_logs_dir(app: &tauri::AppHandle) -> Result<PathBuf, ErrorEnvelope> {
+    Ok(get_app_data_dir(app)?.join("export_logs"))
+}
+
+// Logs are kept this long after their export last wrote to them
+const LOG_RETENTION: std::time::Duration = std::time::Duration::from_secs(30 * 24 * 60 * 60);
+
+// Job ids come back from the webview and end up in file names, so only accept ones we could have made
+fn parse_job_id(job_id: &str) -> Result<String, ErrorEnvelope> {
+    uuid::Uuid::parse_str(job_id)
+        .map(|id| id.to_string())
+        .map_err(|_| ErrorEnvelope::new(
+            "INVALID_REQUEST",
+            &format!("'{}' is not a valid export job id", job_id),
+            "Use the job id returned by export_prepare"
+        ))
+}
+
+// Each job keeps its segments and concat list in a directory of its own
+fn job_temp_dir(app: &tauri::AppHandle, job_id: &str) -> Result<PathBuf, ErrorEnvelope> {
+    Ok(get_app_data_dir(app)?.join("export_temp").join(job_id))
+}
+
+async fn remove_job_files(app: &tauri::AppHandle, job_id: &str) {
+    if let Ok(dir) = job_temp_dir(app, job_id) {
+        let _ = tokio::fs::remove_dir_all(dir).await;
+    }
+}
+
+// Prepared jobs older than this were abandoned, e.g. by closing the export dialog
+const STALE_JOB_AGE: std::time::Duration = std::time::Duration::from_secs(6 * 60 * 60);
+
+// Drop abandoned jobs and delete their segments
+async fn prune_stale_jobs(app: &tauri::AppHandle, jobs: &ExportJobs) {
+    let stale: Vec<String> = {
+        let mut jobs = jobs.lock().unwrap();
+        let stale: Vec<String> = jobs
+            .iter()
+            .filter(|(_, job)| job.created.elapsed() > STALE_JOB_AGE)
+            .map(|(id, _)| id.clone())
+            .collect();
+        for id in &stale {
+            jobs.remove(id);
+        }
+        stale
+    };
+    
+    for id in stale {
+        remove_job_files(app, &id).await;
+    }
+}
+
 // Export: Step 1 - Prepare segments
 #[tauri::command]
 pub async fn export_prepare(
     app: tauri::AppHandle,
     request: ExportRequest,
+    jobs: State<'_, ExportJobs>,
 ) -> Result<ExportPrepareResult, ErrorEnvelope> {
+    let job_id = uuid::Uuid::new_v4().to_string();
+    let logs_dir = export_logs_dir(&app)?;
+    prune_old_files(&logs_dir, "log", LOG_RETENTION).await;
+    let log = JobLog::open(&logs_dir, &job_id).await;
+    let started = std::time::Instant::now();
+    
+    log.section(&format!("export_prepare, job {}", job_id)).await;
+    log.write(&format!("{}\n", serde_json::to_string_pretty(&request).unwrap_or_default())).await;
+    
+    prune_stale_jobs(&app, &jobs).await;
+    let result = prepare_export(&app, request, job_id.clone(), &jobs, &log).await;
+    if result.is_err() {
+        remove_job_files(&app, &job_id).await;
+    }
+    log_outcome(&log, "export_prepare", started, result.as_ref().err()).await;
+    result
+}
+
+// Abandon a prepared export: drop its job and delete its segments
+#[tauri::command]
+pub async fn export_cancel(
+    app: tauri::AppHandle,
+    job_id: String,
+    jobs: State<'_, ExportJobs>,
+) -> Result<(), ErrorEnvelope> {
+    // Only ids of jobs we handed out are turned into paths
+    let removed = jobs.lock().unwrap().remove(&job_id).is_some();
+    if removed {
+        remove_job_files(&app, &job_id).await;
+    }
+    Ok(())
+}
+
+async fn log_outcome(log: &JobLog, stage: &str, started: std::time::Instant, error: Option<&ErrorEnvelope>) {
+    let outcome = match error {
+        Some(e) => format!("failed with {}: {}", e.code, e.message),
+        None => "succeeded".to_string(),
+    };
+    log.section(&format!("{} {} after {:.2}s", stage, outcome, started.elapsed().as_secs_f64())).await;
+}
+
+async fn prepare_export(
+    app: &tauri::AppHandle,
+    request: ExportRequest,
+    job_id: String,
+    jobs: &ExportJobs,
+    log: &JobLog,
+) -> Result<ExportPrepareResult, ErrorEnvelope> {
+    // Validate the whole timeline so problems are reported against the clips the user sees.
+    // Everything after the range is applied works on it alone, so progress and durations are relative to it.
+    let sources = validate_export_request(&request).await?;
+    let (request, sources) = apply_range(request, sources)?;
+    
     // Create temp directory for segments
-    let app_data = get_app_data_dir(&app)?;
-    let export_dir = app_data.join("export_temp");
+    let export_dir = job_temp_dir(app, &job_id)?;
+    
+    tokio::fs::create_dir_all(&export_dir)
+        .await
+        .map_err(|e| ErrorEnvelope::new(
+            "DIR_CREATE_ERROR",
+            &format!("Failed to create export directory: {}", e),
+            "Check application permissions"
+        ))?;
+    
+    check_free_space(&export_dir, estimate_segment_bytes(&request, &sources), "Preparing the export")?;
+    
+    let mut segments: Vec<PreparedSegment> = Vec::new();
+    let mut timeline_ms = 0u64;
+    
+    // Gaps and transitions use the export size, or the first clip's size when exporting at original resolution
+    let (frame_width, frame_height) = match (request.width, request.height) {
+        (Some(width), Some(height)) => (width, height),
+        _ => transformed_size(&request.clips[0], &sources[0]),
+    };
+    let frame_fps = sources[0].fps.unwrap_or(30.0);
+    let gap_color = request.gap_color.as_deref().unwrap_or("black");
+    
+    // Without a re-encoding final stage the watermark is burned into each segment instead
+    let segment_watermark = request.watermark.as_ref().filter(|_| !needs_render(&request));
+    
+    // Generate segments for each clip
+    for (i, (clip, source)) in request.clips.iter().zip(&sources).enumerate() {
+        // Fill any space before this clip so timeline positions survive the concat
+        if clip.start_ms > timeline_ms {
+            let gap_ms = clip.start_ms - timeline_ms;
+            let gap_path = export_dir.join(format!("gap_{:04}.mp4", i));
+            let gap_sec = format!("{:.3}", gap_ms as f64 / 1000.0);
+            
+            let mut graph = FilterGraph::new();
+            graph.add_input(&[
+                "-f",
+                "lavfi",
+                "-i",
+                &format!("color=c={}:s={}x{}:r={}:d={}", gap_color, frame_width, frame_height, frame_fps, gap_sec),
+            ]);
+            graph.add_input(&["-f", "lavfi", "-i", SILENCE_SOURCE]);
+            graph.audio = "1:a:0".to_string();
+            
+            if let Some(watermark) = segment_watermark {
+                if let Some(window) = watermark_window(watermark, timeline_ms, gap_ms) {
+                    apply_watermark(&mut graph, watermark, frame_width, window);
+                }
+            }
+            
+            let mut args = graph.into_args();
+            args.extend(["-t".to_string(), gap_sec]);
+            args.extend(segment_encode_args());
+            if request.web_optimized {
+                args.extend(web_video_args(frame_fps));
+            }
+            args.extend(["-y".to_string(), gap_path.to_string_lossy().to_string()]);
+            
+            run_segment_ffmpeg(&args, &format!("gap before clip {}", i), log).await?;
+            
+            segments.push(PreparedSegment {
+                path: gap_path.to_string_lossy().to_string(),
+                duration_ms: gap_ms,
+                transition_in: None,
+            });
+        }
+        
+        let segment_path = export_dir.join(format!("segment_{:04}.mp4", i));
+        
+        // Timeline duration after speed changes and freezes
+        let duration_ms = clip.duration_ms();
+        let source_ms = clip.out_ms.saturating_sub(clip.in_ms);
+        
+        let start_sec = format!("{:.3}", clip.in_ms as f64 / 1000.0);
+        let source_sec = format!("{:.3}", source_ms as f64 / 1000.0);
+        let duration_sec = format!("{:.3}", duration_ms as f64 / 1000.0);
+        
+        // Build ffmpeg command for segment extraction; the input is limited to the source range
+        // so reverse only has to buffer the clip itself
+        let mut graph = FilterGraph::new();
+        if clip.freeze_ms.is_some() {
+            graph.add_input(&["-ss", &start_sec, "-i", &clip.asset_path]);
+        } else {
+            graph.add_input(&["-ss", &start_sec, "-t", &source_sec, "-i", &clip.asset_path]);
+        }
+        
+        // Sources without audio, and freeze frames, get a silent track so every segment concats cleanly
+        if !source.has_audio || clip.freeze_ms.is_some() {
+            graph.add_input(&["-f", "lavfi", "-i", SILENCE_SOURCE]);
+            graph.audio = "1:a:0".to_string();
+        }
+        
+        if clip.freeze_ms.is_some() {
+            let held = graph.label("freeze");
+            graph.push(format!(
+                "[{}]trim=end_frame=1,setpts=PTS-STARTPTS,tpad=stop_mode=clone:stop_duration={},fps={}[{}]",
+                graph.video, duration_sec, frame_fps, held
+            ));
+            graph.video = held;
+        } else {
+            apply_speed(&mut graph, clip.speed, clip.reverse, source.has_audio);
+        }
+        
+        apply_clip_levels(&mut graph, clip, duration_ms, source.has_audio && clip.freeze_ms.is_none());
+        
+        apply_transform(&mut graph, clip);
+        apply_color(&mut graph, clip);
+        
+        // Add scaling if requested
+        if let (Some(width), Some(height)) = (request.width, request.height) {
+            let scaled = graph.label("scaled");
+            graph.push(format!("[{}]scale={}:{}[{}]", graph.video, width, height, scaled));
+            graph.video = scaled;
+        }
+        
+        if let Some(watermark) = segment_watermark {
+            if let Some(window) = watermark_window(watermark, clip.start_ms, duration_ms) {
+                apply_watermark(&mut graph, watermark, frame_width, window);
+            }
+        }
+        
+        let mut args = graph.into_args();
+        args.extend(["-t".to_string(), duration_sec]);
+        args.extend(segment_encode_args());
+        if request.web_optimized {
+            args.extend(web_video_args(frame_fps));
+        }
+        args.extend(["-y".to_string(), segment_path.to_string_lossy().to_string()]);
+        
+        run_segment_ffmpeg(&args, &format!("segment {}", i), log).await?;
+        
+        timeline_ms = clip.start_ms + duration_ms;
+        
+        // Validation guarantees a transition only ever follows a clip segment, never a gap
+        let transition_in = i
+            .checked_sub(1)
+            .and_then(|before| request.transitions.iter().find(|t| t.after_clip == before))
+            .cloned();
+        
+        segments.push(PreparedSegment {
+            path: segment_path.to_string_lossy().to_string(),
+            duration_ms,
+            transition_in,
+        });
+    }
+    
+    // Transitions overlap neighbouring segments, so each one shortens the export
+    let total_duration_ms = segments.iter().map(|s| s.duration_ms).sum::<u64>()
+        - request.transitions.iter().map(|t| t.duration_ms).sum::<u64>();
+    let segment_paths: Vec<String> = segments.iter().map(|s| s.path.clone()).collect();
+    
+    // Create concat demuxer list file
+    let list_file = export_dir.join("concat_list.txt");
+    let mut list_content = String::new();
+    
+    for segment_path in &segment_paths {
+        list_content.push_str(&format!("file '{}'\n", segment_path));
+    }
+    
+    tokio::fs::write(&list_file, list_content)
+        .await
+        .map_err(|e| ErrorEnvelope::new(
+            "FILE_WRITE_ERROR",
+            &format!("Failed to write concat list: {}", e),
+            "Check application permissions"
+        ))?;
+    
+    jobs.lock().unwrap().insert(job_id.clone(), ExportJob {
+        request,
+        segments,
+        width: frame_width,
+        height: frame_height,
+        fps: frame_fps,
+        created: std::time::Instant::now(),
+    });
+    
+    Ok(ExportPrepareResult {
+        job_id,
+        segment_paths,
+        list_file: list_file.to_string_lossy().to_string(),
+        total_duration_ms,
+    })
+}
+
+// Where the watermark shows within a piece of the output timeline starting at `start_ms`.
+// None: not visible at all; Some(None): visible throughout; Some(Some(window)): local seconds.
+fn watermark_window(watermark: &Watermark, start_ms: u64, duration_ms: u64) -> Option<Option<(f64, f64)>> {
+    let end_ms = start_ms + duration_ms;
+    let shown_from = watermark.start_ms.unwrap_or(0).max(start_ms);
+    let shown_until = watermark.end_ms.unwrap_or(u64::MAX).min(end_ms);
+    
+    if shown_from >= shown_until {
+        return None;
+    }
+    if shown_from == start_ms && shown_until == end_ms {
+        return Some(None);
+    }
+    
+    Some(Some((
+        (shown_from - start_ms) as f64 / 1000.0,
+        (shown_until - start_ms) as f64 / 1000.0,
+    )))
+}
+
+// Whether the final stage has to decode and re-encode instead of stream-copying the segments
+fn needs_render(request: &ExportRequest) -> bool {
+    !request.transitions.is_empty()
+        || request.video_tracks.iter().any(|t| !t.clips.is_empty())
+        || request.audio_tracks.iter().any(|t| !t.clips.is_empty())
+        || !request.text_overlays.is_empty()
+        || request.subtitles.iter().any(|s| s.mode == SubtitleMode::Burn)
+}
+
+// Map a timeline position to the export's time base; each transition pulls later clips earlier
+fn timeline_to_output_ms(request: &ExportRequest, timeline_ms: u64) -> u64 {
+    let shift_ms: u64 = request
+        .transitions
+        .iter()
+        .filter(|t| request.clips[t.after_clip + 1].start_ms <= timeline_ms)
+        .map(|t| t.duration_ms)
+        .sum();
+    timeline_ms.saturating_sub(shift_ms)
+}
+
+// Retime each subtitle file to the export and write it as SRT next to the segments
+async fn prepare_subtitles(
+    request: &ExportRequest,
+    work_dir: &std::path::Path,
+    total_duration_ms: u64,
+) -> Result<Vec<String>, ErrorEnvelope> {
+    let mut paths = Vec::new();
+    
+    for (i, track) in request.subtitles.iter().enumerate() {
+        let cues = read_subtitle_file(&track.path).await?;
+        // Cue times are on the full timeline; the request has already been rebased to its range
+        let offset_ms = request.range.map(|r| r.start_ms).unwrap_or(0);
+        let cues = retime_cues(
+            &cues,
+            |ms| timeline_to_output_ms(request, ms.saturating_sub(offset_ms)),
+            total_duration_ms
+        );
+        
+        let path = work_dir.join(format!("subtitles_{:02}.srt", i));
+        tokio::fs::write(&path, to_srt(&cues))
+            .await
+            .map_err(|e| ErrorEnvelope::new(
+                "FILE_WRITE_ERROR",
+                &format!("Failed to write subtitles: {}", e),
+                "Check application permissions"
+            ))?;
+        paths.push(path.to_string_lossy().to_string());
+    }
+    
+    Ok(paths)
+}
+
+async fn read_subtitle_file(path: &str) -> Result<Vec<SubtitleCue>, ErrorEnvelope> {
+    let content = tokio::fs::read_to_string(path)
+        .await
+        .map_err(|e| ErrorEnvelope::new(
+            "FILE_READ_ERROR",
+            &format!("Failed to read subtitles {}: {}", path, e),
+            "Make sure the subtitle file exists and is UTF-8 text"
+        ))?;
     
-    tokio::fs::create_dir_all(&export_dir)
+    parse_subtitles(&content).map_err(|e| ErrorEnvelope::new(
+        "INVALID_SUBTITLES",
+        &format!("{}: {}", path, e),
+        "Check that the file is valid SRT or WebVTT"
+    ))
+}
+
+// Inputs and filters of the final stage: the joined segments plus everything layered over the whole timeline
+fn final_graph(job: &ExportJob, list_file: &str, total_duration_ms: u64) -> FilterGraph {
+    let mut graph = FilterGraph::new();
+    
+    if job.request.transitions.is_empty() {
+        graph.add_input(&["-f", "concat", "-safe", "0", "-i", list_file]);
+    } else {
+        // Transitions need every segment decoded side by side
+        for segment in &job.segments {
+            graph.add_input(&["-i", &segment.path]);
+        }
+        graph.push(segment_join_graph(&job.segments, job.width, job.height, job.fps));
+        graph.video = "vout".to_string();
+        graph.audio = "aout".to_string();
+    }
+    
+    // Overlay times are on the timeline; transitions pull everything after them earlier
+    let video_tracks: Vec<ExportVideoTrack> = job
+        .request
+        .video_tracks
+        .iter()
+        .map(|track| ExportVideoTrack {
+            clips: track
+                .clips
+                .iter()
+                .map(|clip| TrackClip {
+                    start_ms: timeline_to_output_ms(&job.request, clip.start_ms),
+                    ..clip.clone()
+                })
+                .collect(),
+        })
+        .collect();
+    composite_track_clips(&mut graph, &video_tracks);
+    
+    let text_overlays: Vec<TextOverlay> = job
+        .request
+        .text_overlays
+        .iter()
+        .map(|overlay| TextOverlay {
+            start_ms: timeline_to_output_ms(&job.request, overlay.start_ms),
+            end_ms: timeline_to_output_ms(&job.request, overlay.end_ms),
+            ..overlay.clone()
+        })
+        .collect();
+    draw_text_overlays(&mut graph, &text_overlays);
+    
+    // Without a render the watermark was already burned into the segments
+    if let Some(watermark) = job.request.watermark.as_ref().filter(|_| needs_render(&job.request)) {
+        let watermark = Watermark {
+            start_ms: watermark.start_ms.map(|ms| timeline_to_output_ms(&job.request, ms)),
+            end_ms: watermark.end_ms.map(|ms| timeline_to_output_ms(&job.request, ms)),
+            ..watermark.clone()
+        };
+        if let Some(window) = watermark_window(&watermark, 0, total_duration_ms) {
+            apply_watermark(&mut graph, &watermark, job.width, window);
+        }
+    }
+    
+    let audio_tracks: Vec<ExportAudioTrack> = job
+        .request
+        .audio_tracks
+        .iter()
+        .map(|track| ExportAudioTrack {
+            clips: track
+                .clips
+                .iter()
+                .map(|clip| AudioTrackClip {
+                    start_ms: timeline_to_output_ms(&job.request, clip.start_ms),
+                    ..clip.clone()
+                })
+                .collect(),
+            ..track.clone()
+        })
+        .collect();
+    mix_audio_tracks(&mut graph, &audio_tracks);
+    graph
+}
+
+// Run ffmpeg, emitting export-progress events for `stage` from its time= output.
+// Returns the exit status and the last lines of stderr, where ffmpeg prints summaries and errors.
+async fn run_ffmpeg_with_progress(
+    app: &tauri::AppHandle,
+    args: &[String],
+    stage: &str,
+    label: &str,
+    total_duration_ms: u64,
+    log: &JobLog,
+) -> Result<(std::process::ExitStatus, String), ErrorEnvelope> {
+    use tokio::io::{AsyncBufReadExt, BufReader};
+    
+    let ffmpeg = get_ffmpeg_path();
+    let started = std::time::Instant::now();
+    let mut child = tokio::process::Command::new(&ffmpeg)
+        .args(args)
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .map_err(|e| ErrorEnvelope::new(
+            "FFMPEG_ERROR",
+            &format!("Failed to start ffmpeg: {}", e),
+            "Make sure FFmpeg is installed"
+        ))?;
+    
+    let mut tail: std::collections::VecDeque<String> = std::collections::VecDeque::new();
+    let mut full_stderr = String::new();
+    
+    // Read stderr for progress
+    if let Some(stderr) = child.stderr.take() {
+        let reader = BufReader::new(stderr);
+        let mut lines = reader.lines();
+        
+        while let Ok(Some(line)) = lines.next_line().await {
+            // Parse progress from ffmpeg output (time=hh:mm:ss.xx)
+            if line.contains("time=") {
+                if let Some(time_str) = line.split("time=").nth(1) {
+                    if let Some(time_part) = time_str.split_whitespace().next() {
+                        if let Some(current_ms) = parse_ffmpeg_time(time_part) {
+                            let progress = (current_ms as f32) / (total_duration_ms as f32);
+                            let progress = progress.min(1.0);
+                            
+                            let _ = app.emit_to(
+                                tauri::EventTarget::Any,
+                                "export-progress",
+                                ExportProgress {
+                                    stage: stage.to_string(),
+                                    progress,
+                                    current_ms,
+                                    total_ms: total_duration_ms,
+                                    message: format!("{}... {:.0}%", label, progress * 100.0),
+                                }
+                            );
+                        }
+                    }
+                }
+            }
+            
+            full_stderr.push_str(&line);
+            full_stderr.push('\n');
+            if tail.len() == 40 {
+                tail.pop_front();
+            }
+            tail.push_back(line);
+        }
+    }
+    
+    // Wait for process to complete
+    let status = child.wait().await;
+    let exit_code = status.as_ref().ok().and_then(|s| s.code());
+    log.command(label, &ffmpeg.to_string_lossy(), args, exit_code, started.elapsed(), &full_stderr).await;
+    
+    let status = status.map_err(|e| ErrorEnvelope::new(
+        "FFMPEG_ERROR",
+        &format!("FFmpeg process error: {}", e),
+        "Export may have been interrupted"
+    ))?;
+    
+    Ok((status, Vec::from(tail).join("\n")))
+}
+
+// First loudnorm pass: run the final graph's audio through loudnorm in analysis mode
+async fn measure_loudness(
+    app: &tauri::AppHandle,
+    mut graph: FilterGraph,
+    target: &LoudnessTarget,
+    total_duration_ms: u64,
+    log: &JobLog,
+) -> Result<LoudnessMeasurement, ErrorEnvelope> {
+    let measured = graph.label("measured");
+    graph.push(format!(
+        "[{}]loudnorm=I={}:TP={}:LRA={}:print_format=json[{}]",
+        graph.audio, target.integrated_lufs, target.true_peak_db, target.lra, measured
+    ));
+    graph.audio = measured;
+    
+    let mut args = graph.into_audio_args();
+    args.extend(["-f".to_string(), "null".to_string(), "-".to_string()]);
+    
+    let (status, stderr) = run_ffmpeg_with_progress(app, &args, "loudness", "Measuring loudness", total_duration_ms, log).await?;
+    
+    if !status.success() {
+        return Err(ErrorEnvelope::new(
+            "LOUDNESS_FAILED",
+            &format!("Loudness measurement failed: {}", stderr),
+            "Check that the timeline has valid audio"
+        ));
+    }
+    
+    parse_loudnorm_summary(&stderr).ok_or_else(|| ErrorEnvelope::new(
+        "LOUDNESS_FAILED",
+        "Could not read the loudness measurement from ffmpeg",
+        "Make sure your FFmpeg build includes the loudnorm filter"
+    ))
+}
+
+// Whether export_concat has to build a filter graph at all, rather than only joining segments
+fn needs_final_graph(request: &ExportRequest) -> bool {
+    needs_render(request)
+        || request.loudness.is_some()
+        || !request.subtitles.is_empty()
+        || request.target_size.is_some()
+        || request.streaming.is_some()
+}
+
+fn has_container_metadata(request: &ExportRequest) -> bool {
+    request.metadata.is_some() || !request.chapters.is_empty() || request.chapters_per_clip
+}
+
+// Chapter starts in output time, from the markers or one per clip
+fn chapter_starts(request: &ExportRequest) -> Vec<(u64, String)> {
+    if request.chapters_per_clip {
+        return request
+            .clips
+            .iter()
+            .map(|clip| {
+                let path = std::path::Path::new(&clip.asset_path);
+                let name = path.file_stem().unwrap_or(path.as_os_str()).to_string_lossy().to_string();
+                (timeline_to_output_ms(request, clip.start_ms), name)
+            })
+            .collect();
+    }
+    
+    // Markers are timeline times; ones before a range start open the export instead
+    let offset_ms = request.range.map(|r| r.start_ms).unwrap_or(0);
+    request
+        .chapters
+        .iter()
+        .map(|c| (timeline_to_output_ms(request, c.start_ms.saturating_sub(offset_ms)), c.title.clone()))
+        .collect()
+}
+
+// Take the tags and chapters from the ffmetadata input and tag the streams with the language
+fn metadata_args(request: &ExportRequest, input: usize) -> Vec<String> {
+    let mut args = vec![
+        "-map_metadata".to_string(),
+        input.to_string(),
+        "-map_chapters".to_string(),
+        input.to_string(),
+    ];
+    if let Some(language) = request.metadata.as_ref().and_then(|m| m.language.as_ref()) {
+        args.extend([
+            "-metadata:s:v:0".to_string(),
+            format!("language={}", language),
+            "-metadata:s:a:0".to_string(),
+            format!("language={}", language),
+        ]);
+    }
+    args
+}
+
+// Write the tags and chapters as an ffmetadata file in the work dir
+async fn prepare_metadata(
+    request: &ExportRequest,
+    work_dir: &std::path::Path,
+    total_duration_ms: u64,
+    log: &JobLog,
+) -> Result<String, ErrorEnvelope> {
+    let mut tags = Vec::new();
+    if let Some(metadata) = &request.metadata {
+        let fields = [
+            ("title", &metadata.title),
+            ("artist", &metadata.artist),
+            ("comment", &metadata.comment),
+            ("creation_time", &metadata.creation_time),
+        ];
+        for (key, value) in fields {
+            if let Some(value) = value {
+                tags.push((key, value.as_str()));
+            }
+        }
+    }
+    
+    let chapters = chapters_from_starts(chapter_starts(request), total_duration_ms);
+    let content = to_ffmetadata(&tags, &chapters);
+    log.write(&format!("ffmetadata:\n{}\n", content)).await;
+    
+    let path = work_dir.join("metadata.txt");
+    tokio::fs::write(&path, content)
         .await
         .map_err(|e| ErrorEnvelope::new(
-            "DIR_CREATE_ERROR",
-            &format!("Failed to create export directory: {}", e),
+            "FILE_WRITE_ERROR",
+            &format!("Failed to write metadata: {}", e),
             "Check application permissions"
         ))?;
     
-    let mut segment_paths = Vec::new();
-    let mut total_duration_ms = 0u64;
+    Ok(path.to_string_lossy().to_string())
+}
+
+// Final-stage graph with loudness normalization and subtitles applied, ready to encode
+struct FinalStage {
+    graph: FilterGraph,
+    soft_subtitles: Vec<(usize, Option<String>)>,  // Subtitle input index and language
+    metadata_input: Option<usize>,  // ffmetadata input with the tags and chapters
+    loudness: Option<LoudnessMeasurement>,
+    work_dir: PathBuf,  // Next to the segments; holds subtitles and two-pass logs
+}
+
+async fn build_final_stage(
+    app: &tauri::AppHandle,
+    job: &ExportJob,
+    work_dir: &std::path::Path,
+    list_file: &str,
+    total_duration_ms: u64,
+    log: &JobLog,
+) -> Result<FinalStage, ErrorEnvelope> {
+    let mut graph = final_graph(job, list_file, total_duration_ms);
+    let mut loudness = None;
+    
+    if let Some(target) = &job.request.loudness {
+        let measurement = measure_loudness(app, graph.clone(), target, total_duration_ms, log).await?;
+        
+        // Silence measures as -inf and can't be normalized; it is exported as-is
+        if measurement.integrated_lufs.is_finite() {
+            let normalized = graph.label("normalized");
+            graph.push(format!(
+                "[{}]loudnorm=I={}:TP={}:LRA={}:measured_I={}:measured_TP={}:measured_LRA={}:measured_thresh={}:offset={}:linear=true,aresample=48000[{}]",
+                graph.audio,
+                target.integrated_lufs,
+                target.true_peak_db,
+                target.lra,
+                measurement.integrated_lufs,
+                measurement.true_peak_db,
+                measurement.lra,
+                measurement.threshold_lufs,
+                measurement.target_offset_db,
+                normalized
+            ));
+            graph.audio = normalized;
+        }
+        loudness = Some(measurement);
+    }
+    
+    let subtitle_paths = prepare_subtitles(&job.request, work_dir, total_duration_ms).await?;
+    let mut soft_subtitles = Vec::new();
+    
+    for (track, path) in job.request.subtitles.iter().zip(&subtitle_paths) {
+        match track.mode {
+            SubtitleMode::Burn => burn_subtitles(&mut graph, path, &track.style),
+            SubtitleMode::Soft => {
+                let input = graph.add_input(&["-i", path]);
+                soft_subtitles.push((input, track.language.clone()));
+            }
+        }
+    }
+    
+    let metadata_input = if has_container_metadata(&job.request) {
+        let path = prepare_metadata(&job.request, work_dir, total_duration_ms, log).await?;
+        Some(graph.add_input(&["-f", "ffmetadata", "-i", &path]))
+    } else {
+        None
+    };
+    
+    Ok(FinalStage {
+        graph,
+        soft_subtitles,
+        metadata_input,
+        loudness,
+        work_dir: work_dir.to_path_buf(),
+    })
+}
+
+// Arguments for the final encode. `codecs` overrides the default of re-encoding only what the graph changed.
+fn final_output_args(job: &ExportJob, stage: &FinalStage, output_path: &str, codecs: Option<Vec<String>>) -> Vec<String> {
+    let mut args = stage.graph.clone().into_args();
+    let encodes_video = codecs.is_some() || needs_render(&job.request);
+    
+    match codecs {
+        Some(codecs) => args.extend(codecs),
+        None => {
+            // Streams nothing was drawn over or mixed into are copied as-is
+            if needs_render(&job.request) {
+                args.extend(video_encode_args());
+            } else {
+                args.extend(["-c:v".to_string(), "copy".to_string()]);
+            }
+            if needs_render(&job.request) || job.request.loudness.is_some() {
+                args.extend(audio_encode_args());
+            } else {
+                args.extend(["-c:a".to_string(), "copy".to_string()]);
+            }
+        }
+    }
+    
+    // Copied video already has the web GOP from its segments
+    if job.request.web_optimized {
+        if encodes_video {
+            args.extend(web_video_args(job.fps));
+        }
+        args.extend(web_output_args(output_path));
+    }
+    
+    // MP4/MOV only carry mov_text; WebM needs WebVTT
+    let subtitle_codec = if output_path.to_lowercase().ends_with(".webm") { "webvtt" } else { "mov_text" };
+    for (n, (input, language)) in stage.soft_subtitles.iter().enumerate() {
+        args.extend([
+            "-map".to_string(),
+            format!("{}:s:0", input),
+            format!("-c:s:{}", n),
+            subtitle_codec.to_string(),
+        ]);
+        if let Some(language) = language {
+            args.extend([format!("-metadata:s:s:{}", n), format!("language={}", language)]);
+        }
+    }
+    
+    if let Some(input) = stage.metadata_input {
+        args.extend(metadata_args(&job.request, input));
+    }
+    
+    args.extend(["-y".to_string(), output_path.to_string()]);
+    args
+}
+
+// Run the encode that writes the output file
+async fn run_final_encode(app: &tauri::AppHandle, args: &[String], total_duration_ms: u64, log: &JobLog) -> Result<(), ErrorEnvelope> {
+    let (status, _) = run_ffmpeg_with_progress(app, args, "concat", "Exporting", total_duration_ms, log).await?;
+    
+    if !status.success() {
+        return Err(ErrorEnvelope::new(
+            "EXPORT_FAILED",
+            "FFmpeg export failed",
+            "Check if output path is writable and source files are valid"
+        ));
+    }
+    
+    Ok(())
+}
+
+// Video bitrate below which a size target isn't worth attempting
+const MIN_VIDEO_KBPS: u32 = 100;
+
+// Two-pass rate control arguments for one pass of the target-size encode
+fn two_pass_video_args(codec: VideoCodec, video_kbps: u32, pass: u32, passlog: &str) -> Vec<String> {
+    let mut args: Vec<String> = match codec {
+        VideoCodec::H264 => vec![
+            "-c:v".to_string(),
+            "libx264".to_string(),
+            "-pass".to_string(),
+            pass.to_string(),
+            "-passlogfile".to_string(),
+            passlog.to_string(),
+        ],
+        // libx265 takes its pass settings through x265-params; hvc1 lets Apple players open it
+        VideoCodec::H265 => vec![
+            "-c:v".to_string(),
+            "libx265".to_string(),
+            "-x265-params".to_string(),
+            format!("pass={}:stats={}.log", pass, passlog),
+            "-tag:v".to_string(),
+            "hvc1".to_string(),
+        ],
+    };
+    args.extend([
+        "-preset".to_string(),
+        "medium".to_string(),
+        "-b:v".to_string(),
+        format!("{}k", video_kbps),
+        "-pix_fmt".to_string(),
+        "yuv420p".to_string(),
+    ]);
+    args
+}
+
+// Encode with two-pass rate control until the output fits the target size, lowering the
+// bitrate by the overshoot after each miss. Returns the final file size.
+async fn encode_to_target_size(
+    app: &tauri::AppHandle,
+    job: &ExportJob,
+    stage: &FinalStage,
+    output_path: &str,
+    total_duration_ms: u64,
+    target: &TargetSize,
+    log: &JobLog,
+) -> Result<u64, ErrorEnvelope> {
+    // Leave ~3% for container overhead
+    let total_kbps = (target.size_bytes as f64 * 8.0 / 1000.0) / (total_duration_ms as f64 / 1000.0) * 0.97;
+    let mut video_kbps = (total_kbps - target.audio_bitrate_kbps as f64).max(0.0) as u32;
+    
+    if video_kbps < MIN_VIDEO_KBPS {
+        return Err(ErrorEnvelope::new(
+            "TARGET_SIZE_TOO_SMALL",
+            &format!(
+                "{} bytes leaves only {}kbps for video over {}ms",
+                target.size_bytes, video_kbps, total_duration_ms
+            ),
+            "Choose a larger target size, a lower audio bitrate or a shorter timeline"
+        ));
+    }
+    
+    let passlog = stage.work_dir.join("target_size").to_string_lossy().to_string();
+    let mut size_bytes = 0;
+    
+    for _ in 0..3 {
+        // Pass 1 only analyses the video
+        let mut analysis = stage.graph.clone().into_video_args();
+        analysis.extend(two_pass_video_args(target.codec, video_kbps, 1, &passlog));
+        if job.request.web_optimized {
+            analysis.extend(web_video_args(job.fps));
+        }
+        analysis.extend(["-f".to_string(), "null".to_string(), "-".to_string()]);
+        
+        let (status, stderr) = run_ffmpeg_with_progress(app, &analysis, "analysis", "Analyzing", total_duration_ms, log).await?;
+        if !status.success() {
+            return Err(ErrorEnvelope::new(
+                "EXPORT_FAILED",
+                &format!("First encoding pass failed: {}", stderr),
+                "Check that your FFmpeg build includes the selected codec"
+            ));
+        }
+        
+        let mut codecs = two_pass_video_args(target.codec, video_kbps, 2, &passlog);
+        codecs.extend([
+            "-c:a".to_string(),
+            "aac".to_string(),
+            "-b:a".to_string(),
+            format!("{}k", target.audio_bitrate_kbps),
+        ]);
+        run_final_encode(app, &final_output_args(job, stage, output_path, Some(codecs)), total_duration_ms, log).await?;
+        
+        size_bytes = file_size(output_path).await?;
+        if size_bytes <= target.size_bytes {
+            return Ok(size_bytes);
+        }
+        
+        video_kbps = (video_kbps as f64 * target.size_bytes as f64 / size_bytes as f64 * 0.95) as u32;
+        if video_kbps < MIN_VIDEO_KBPS {
+            break;
+        }
+    }
+    
+    Err(ErrorEnvelope::new(
+        "TARGET_SIZE_MISSED",
+        &format!("Export is {} bytes, over the {} byte target", size_bytes, target.size_bytes),
+        "Try a larger target size or a lower audio bitrate"
+    ))
+}
+
+// Total size of the files under a streaming output directory
+fn dir_size(dir: &std::path::Path) -> u64 {
+    std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| match entry.metadata() {
+                    Ok(m) if m.is_dir() => dir_size(&entry.path()),
+                    Ok(m) => m.len(),
+                    Err(_) => 0,
+                })
+                .sum()
+        })
+        .unwrap_or(0)
+}
+
+async fn remove_output(path: &str) {
+    if std::path::Path::new(path).is_dir() {
+        let _ = tokio::fs::remove_dir_all(path).await;
+    } else {
+        let _ = tokio::fs::remove_file(path).await;
+    }
+}
+
+// Move a finished render into place. A file rename replaces the old export directly; a streaming
+// directory can't be renamed over, so the old one is moved aside first and removed afterwards.
+async fn replace_output(temp_path: &str, output_path: &str) -> Result<(), ErrorEnvelope> {
+    let move_error = |e: std::io::Error| ErrorEnvelope::new(
+        "FILE_WRITE_ERROR",
+        &format!("Failed to move the export into place at {}: {}", output_path, e),
+        "Check that the output isn't open in another application"
+    );
+    
+    if std::path::Path::new(output_path).is_dir() {
+        let previous = format!("{}.old", temp_output_path(output_path));
+        remove_output(&previous).await;
+        tokio::fs::rename(output_path, &previous).await.map_err(move_error)?;
+        tokio::fs::rename(temp_path, output_path).await.map_err(move_error)?;
+        remove_output(&previous).await;
+        return Ok(());
+    }
+    
+    tokio::fs::rename(temp_path, output_path).await.map_err(move_error)
+}
+
+async fn file_size(path: &str) -> Result<u64, ErrorEnvelope> {
+    tokio::fs::metadata(path)
+        .await
+        .map(|m| m.len())
+        .map_err(|e| ErrorEnvelope::new(
+            "FILE_READ_ERROR",
+            &format!("Failed to read exported file {}: {}", path, e),
+            "Check that the output location is accessible"
+        ))
+}
+
+// Container and codec padding add a little length, so durations may drift by 1% (at least 250ms)
+fn duration_tolerance_ms(total_duration_ms: u64) -> u64 {
+    (total_duration_ms / 100).max(250)
+}
+
+// Probe the finished file and check it has the streams, size and length the export was meant to produce
+async fn verify_output(
+    output_path: &str,
+    total_duration_ms: u64,
+    job: Option<&ExportJob>,
+) -> Result<ExportVerification, ErrorEnvelope> {
+    let probe_data = run_ffprobe(output_path).await.map_err(|e| ErrorEnvelope::new(
+        "EXPORT_VERIFY_FAILED",
+        &format!("Could not read the exported file: {}", e.message),
+        "The export may be corrupt; try exporting again"
+    ))?;
+    
+    let video_stream = probe_data.streams.iter().find(|s| s.codec_type == "video");
+    let verification = ExportVerification {
+        duration_ms: probe_data.format.duration
+            .and_then(|d| d.parse::<f64>().ok())
+            .map(|d| (d * 1000.0) as u64)
+            .unwrap_or(0),
+        expected_duration_ms: total_duration_ms,
+        width: video_stream.and_then(|s| s.width),
+        height: video_stream.and_then(|s| s.height),
+        expected_width: job.map(|j| j.width),
+        expected_height: job.map(|j| j.height),
+        has_video: video_stream.is_some(),
+        has_audio: probe_data.streams.iter().any(|s| s.codec_type == "audio"),
+        subtitle_streams: probe_data.streams.iter().filter(|s| s.codec_type == "subtitle").count(),
+        playlists: Vec::new(),
+    };
+    
+    let mut problems = Vec::new();
+    
+    if verification.duration_ms.abs_diff(total_duration_ms) > duration_tolerance_ms(total_duration_ms) {
+        problems.push(format!(
+            "Duration is {}ms but {}ms was expected",
+            verification.duration_ms, total_duration_ms
+        ));
+    }
+    
+    // Every segment carries audio (silence where the source had none), so the output must too
+    if !verification.has_video {
+        problems.push("There is no video stream".to_string());
+    }
+    if !verification.has_audio {
+        problems.push("There is no audio stream".to_string());
+    }
+    
+    if let Some(job) = job {
+        if verification.has_video && (verification.width, verification.height) != (Some(job.width), Some(job.height)) {
+            problems.push(format!(
+                "Resolution is {}x{} but {}x{} was expected",
+                verification.width.unwrap_or(0),
+                verification.height.unwrap_or(0),
+                job.width,
+                job.height
+            ));
+        }
+        
+        let soft_subtitles = job.request.subtitles.iter().filter(|s| s.mode == SubtitleMode::Soft).count();
+        if verification.subtitle_streams != soft_subtitles {
+            problems.push(format!(
+                "Found {} subtitle stream(s) but {} were expected",
+                verification.subtitle_streams, soft_subtitles
+            ));
+        }
+    }
+    
+    if !problems.is_empty() {
+        return Err(ErrorEnvelope::new(
+            "EXPORT_VERIFY_FAILED",
+            &format!("The exported file is incomplete:\n{}", problems.join("\n")),
+            "Check the source files and try exporting again"
+        ));
+    }
+    
+    Ok(verification)
+}
+
+fn rendition_name(rendition: &StreamingRendition) -> String {
+    format!("{}p", rendition.height)
+}
+
+// Encode the bitrate ladder into `output_dir` in one ffmpeg run. Keyframes are forced on the
+// segment grid with scene-cut detection off, so every rendition's segments start on the same frames.
+async fn encode_streaming(
+    app: &tauri::AppHandle,
+    job: &ExportJob,
+    stage: &FinalStage,
+    output_dir: &str,
+    total_duration_ms: u64,
+    streaming: &StreamingOutput,
+    log: &JobLog,
+) -> Result<(), ErrorEnvelope> {
+    tokio::fs::create_dir_all(output_dir)
+        .await
+        .map_err(|e| ErrorEnvelope::new(
+            "DIR_CREATE_ERROR",
+            &format!("Failed to create streaming output directory: {}", e),
+            "Check that the output location is writable"
+        ))?;
+    
+    let mut graph = stage.graph.clone();
+    let count = streaming.renditions.len();
+    
+    let splits: Vec<String> = (0..count).map(|_| graph.label("rendition")).collect();
+    graph.push(format!(
+        "[{}]split={}{}",
+        graph.video,
+        count,
+        splits.iter().map(|l| format!("[{}]", l)).collect::<String>()
+    ));
+    let mut video_outputs = Vec::new();
+    for (rendition, split) in streaming.renditions.iter().zip(&splits) {
+        let scaled = graph.label("scaled");
+        graph.push(format!("[{}]scale=-2:{}[{}]", split, rendition.height, scaled));
+        video_outputs.push(scaled);
+    }
+    
+    // HLS variants each carry their own audio; DASH shares one audio representation
+    let audio_count = match streaming.format {
+        StreamingFormat::Hls => count,
+        StreamingFormat::Dash => 1,
+    };
+    let audio_outputs: Vec<String> = (0..audio_count).map(|_| graph.label("rendition_audio")).collect();
+    graph.push(format!(
+        "[{}]asplit={}{}",
+        graph.audio,
+        audio_count,
+        audio_outputs.iter().map(|l| format!("[{}]", l)).collect::<String>()
+    ));
+    
+    let mut args = graph.into_mapped_args(&[video_outputs, audio_outputs].concat());
+    
+    let gop = (job.fps * streaming.segment_duration_s as f64).round().max(1.0) as u32;
+    args.extend([
+        "-preset", "medium",
+        "-pix_fmt", "yuv420p",
+        "-ar", "48000",
+        "-ac", "2",
+        "-sc_threshold", "0",
+    ].iter().map(|s| s.to_string()));
+    args.extend([
+        "-g".to_string(),
+        gop.to_string(),
+        "-keyint_min".to_string(),
+        gop.to_string(),
+        "-force_key_frames".to_string(),
+        format!("expr:gte(t,n_forced*{})", streaming.segment_duration_s),
+    ]);
+    
+    for (i, rendition) in streaming.renditions.iter().enumerate() {
+        let kbps = rendition.video_bitrate_kbps;
+        args.extend([
+            format!("-c:v:{}", i),
+            "libx264".to_string(),
+            format!("-b:v:{}", i),
+            format!("{}k", kbps),
+            format!("-maxrate:v:{}", i),
+            format!("{}k", kbps * 107 / 100),
+            format!("-bufsize:v:{}", i),
+            format!("{}k", kbps * 3 / 2),
+        ]);
+    }
+    for (i, rendition) in streaming.renditions.iter().take(audio_count).enumerate() {
+        args.extend([
+            format!("-c:a:{}", i),
+            "aac".to_string(),
+            format!("-b:a:{}", i),
+            format!("{}k", rendition.audio_bitrate_kbps),
+        ]);
+    }
+    
+    let dir = std::path::Path::new(output_dir);
+    let segment_sec = streaming.segment_duration_s.to_string();
+    match streaming.format {
+        // <dir>/master.m3u8 and <dir>/<height>p/playlist.m3u8 + segment_NNNNN.ts
+        StreamingFormat::Hls => {
+            let stream_map = streaming
+                .renditions
+                .iter()
+                .enumerate()
+                .map(|(i, r)| format!("v:{},a:{},name:{}", i, i, rendition_name(r)))
+                .collect::<Vec<_>>()
+                .join(" ");
+            args.extend([
+                "-f".to_string(),
+                "hls".to_string(),
+                "-hls_time".to_string(),
+                segment_sec,
+                "-hls_playlist_type".to_string(),
+                "vod".to_string(),
+                "-hls_flags".to_string(),
+                "independent_segments".to_string(),
+                "-hls_segment_filename".to_string(),
+                dir.join("%v").join("segment_%05d.ts").to_string_lossy().to_string(),
+                "-master_pl_name".to_string(),
+                "master.m3u8".to_string(),
+                "-var_stream_map".to_string(),
+                stream_map,
+                "-y".to_string(),
+                dir.join("%v").join("playlist.m3u8").to_string_lossy().to_string(),
+            ]);
+        }
+        // <dir>/manifest.mpd with init-<id>.m4s and chunk-<id>-NNNNN.m4s beside it
+        StreamingFormat::Dash => {
+            args.extend([
+                "-f".to_string(),
+                "dash".to_string(),
+                "-seg_duration".to_string(),
+                segment_sec,
+                "-use_template".to_string(),
+                "1".to_string(),
+                "-use_timeline".to_string(),
+                "0".to_string(),
+                "-adaptation_sets".to_string(),
+                "id=0,streams=v id=1,streams=a".to_string(),
+                "-init_seg_name".to_string(),
+                "init-$RepresentationID$.m4s".to_string(),
+                "-media_seg_name".to_string(),
+                "chunk-$RepresentationID$-$Number%05d$.m4s".to_string(),
+                "-y".to_string(),
+                dir.join("manifest.mpd").to_string_lossy().to_string(),
+            ]);
+        }
+    }
+    
+    run_final_encode(app, &args, total_duration_ms, log).await
+}
+
+async fn read_manifest(dir: &std::path::Path, name: &str) -> Result<String, ErrorEnvelope> {
+    tokio::fs::read_to_string(dir.join(name))
+        .await
+        .map_err(|e| ErrorEnvelope::new(
+            "EXPORT_VERIFY_FAILED",
+            &format!("Could not read {}: {}", name, e),
+            "The export may be incomplete; try exporting again"
+        ))
+}
+
+// Read back the generated playlists and check the ladder, the segment grid and the duration
+async fn verify_streaming_output(
+    output_dir: &str,
+    total_duration_ms: u64,
+    streaming: &StreamingOutput,
+) -> Result<ExportVerification, ErrorEnvelope> {
+    let dir = std::path::Path::new(output_dir);
+    let segment_ms = streaming.segment_duration_s as u64 * 1000;
+    let invalid = |name: &str, e: String| ErrorEnvelope::new(
+        "EXPORT_VERIFY_FAILED",
+        &format!("{} is invalid: {}", name, e),
+        "The export may be incomplete; try exporting again"
+    );
+    
+    let mut problems = Vec::new();
+    let mut playlists = Vec::new();
+    // Size of each video rendition found, in ladder order
+    let mut found_sizes: Vec<(Option<u32>, Option<u32>)> = Vec::new();
+    let mut durations_ms = Vec::new();
+    let has_audio;
     
-    // Generate segments for each clip
-    for (i, clip) in request.clips.iter().enumerate() {
-        // Validate file exists
-        if !std::path::Path::new(&clip.asset_path).exists() {
-            return Err(ErrorEnvelope::new(
-                "FILE_NOT_FOUND",
-                &format!("Source file not found: {}", clip.asset_path),
-                "Make sure all source files are available"
-            ));
+    match streaming.format {
+        StreamingFormat::Hls => {
+            let master = parse_hls_master(&read_manifest(dir, "master.m3u8").await?)
+                .map_err(|e| invalid("master.m3u8", e))?;
+            playlists.push("master.m3u8".to_string());
+            has_audio = master.iter().all(|v| v.codecs.as_deref().is_some_and(|c| c.contains("mp4a")));
+            
+            for variant in &master {
+                let media = parse_hls_media(&read_manifest(dir, &variant.uri).await?)
+                    .map_err(|e| invalid(&variant.uri, e))?;
+                let variant_path = dir.join(&variant.uri);
+                let variant_dir = variant_path.parent().unwrap_or(dir);
+                
+                if !media.ended {
+                    problems.push(format!("{} has no end marker", variant.uri));
+                }
+                
+                // Every segment but the last should sit on the grid, give or take a frame
+                let last = media.segments.len().saturating_sub(1);
+                for (i, (uri, duration_ms)) in media.segments.iter().enumerate() {
+                    if i < last && duration_ms.abs_diff(segment_ms) > 100 {
+                        problems.push(format!("{}: {} is {}ms, not {}ms", variant.uri, uri, duration_ms, segment_ms));
+                    }
+                    if !variant_dir.join(uri).exists() {
+                        problems.push(format!("{}: {} is missing", variant.uri, uri));
+                    }
+                }
+                
+                durations_ms.push(media.segments.iter().map(|(_, d)| d).sum::<u64>());
+                found_sizes.push(variant.resolution.map(|(w, h)| (Some(w), Some(h))).unwrap_or((None, None)));
+                playlists.push(variant.uri.clone());
+            }
         }
-        
-        let segment_path = export_dir.join(format!("segment_{:04}.mp4", i));
-        
-        // Calculate duration and times in seconds
-        let duration_ms = clip.out_ms - clip.in_ms;
-        total_duration_ms += duration_ms;
-        
-        let start_sec = clip.in_ms as f64 / 1000.0;
-        let duration_sec = duration_ms as f64 / 1000.0;
-        
-        // Build ffmpeg command for segment extraction
-        let mut args = vec![
-            "-ss".to_string(),
-            format!("{:.3}", start_sec),
-            "-i".to_string(),
-            clip.asset_path.clone(),
-            "-t".to_string(),
-            format!("{:.3}", duration_sec),
-        ];
-        
-        // Add scaling if requested
-        if let (Some(width), Some(height)) = (request.width, request.height) {
-            args.extend_from_slice(&[
-                "-vf".to_string(),
-                format!("scale={}:{}", width, height),
-            ]);
+        StreamingFormat::Dash => {
+            let manifest = parse_dash_manifest(&read_manifest(dir, "manifest.mpd").await?)
+                .map_err(|e| invalid("manifest.mpd", e))?;
+            playlists.push("manifest.mpd".to_string());
+            
+            let is_kind = |r: &DashRepresentation, kind: &str| r.mime_type.as_deref().is_some_and(|m| m.starts_with(kind));
+            has_audio = manifest.representations.iter().any(|r| is_kind(r, "audio"));
+            for representation in manifest.representations.iter().filter(|r| is_kind(r, "video")) {
+                found_sizes.push((representation.width, representation.height));
+            }
+            durations_ms.push(manifest.duration_ms.unwrap_or(0));
         }
-        
-        // Re-encode to H.264/AAC for compatibility
-        args.extend_from_slice(&[
-            "-c:v".to_string(),
-            "libx264".to_string(),
-            "-preset".to_string(),
-            "medium".to_string(),
-            "-crf".to_string(),
-            "23".to_string(),
-            "-c:a".to_string(),
-            "aac".to_string(),
-            "-b:a".to_string(),
-            "192k".to_string(),
-            "-y".to_string(),
-            segment_path.to_str().unwrap().to_string(),
-        ]);
-        
-        // Execute ffmpeg
-        let output = tokio::process::Command::new(get_ffmpeg_path())
-            .args(&args)
-            .output()
-            .await
-            .map_err(|e| ErrorEnvelope::new(
-                "FFMPEG_ERROR",
-                &format!("Failed to run ffmpeg: {}", e),
-                "Make sure FFmpeg is installed: brew install ffmpeg"
-            ))?;
-        
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            return Err(ErrorEnvelope::new(
-                "SEGMENT_FAILED",
-                &format!("Failed to create segment {}: {}", i, stderr),
-                "Check if the source file is valid"
+    }
+    
+    if found_sizes.len() != streaming.renditions.len() {
+        problems.push(format!(
+            "Found {} rendition(s) but {} were expected",
+            found_sizes.len(),
+            streaming.renditions.len()
+        ));
+    }
+    for (rendition, (_, height)) in streaming.renditions.iter().zip(&found_sizes) {
+        if *height != Some(rendition.height) {
+            problems.push(format!(
+                "The {} rendition came out {} pixels high",
+                rendition_name(rendition),
+                height.map(|h| h.to_string()).unwrap_or_else(|| "an unknown number of".to_string())
             ));
         }
-        
-        segment_paths.push(segment_path.to_string_lossy().to_string());
+    }
+    if !has_audio {
+        problems.push("There is no audio stream".to_string());
     }
     
-    // Create concat demuxer list file
-    let list_file = export_dir.join("concat_list.txt");
-    let mut list_content = String::new();
-    
-    for segment_path in &segment_paths {
-        list_content.push_str(&format!("file '{}'\n", segment_path));
+    // The shortest rendition decides when playback stops
+    let duration_ms = durations_ms.iter().copied().min().unwrap_or(0);
+    if duration_ms.abs_diff(total_duration_ms) > duration_tolerance_ms(total_duration_ms) {
+        problems.push(format!("Duration is {}ms but {}ms was expected", duration_ms, total_duration_ms));
     }
     
-    tokio::fs::write(&list_file, list_content)
-        .await
-        .map_err(|e| ErrorEnvelope::new(
-            "FILE_WRITE_ERROR",
-            &format!("Failed to write concat list: {}", e),
-            "Check application permissions"
-        ))?;
+    if !problems.is_empty() {
+        return Err(ErrorEnvelope::new(
+            "EXPORT_VERIFY_FAILED",
+            &format!("The streaming export is incomplete:\n{}", problems.join("\n")),
+            "Check the source files and try exporting again"
+        ));
+    }
     
-    Ok(ExportPrepareResult {
-        segment_paths,
-        list_file: list_file.to_string_lossy().to_string(),
-        total_duration_ms,
+    let (width, height) = found_sizes.first().copied().unwrap_or((None, None));
+    Ok(ExportVerification {
+        duration_ms,
+        expected_duration_ms: total_duration_ms,
+        width,
+        height,
+        expected_width: None,
+        expected_height: streaming.renditions.first().map(|r| r.height),
+        has_video: !found_sizes.is_empty(),
+        has_audio,
+        subtitle_streams: 0,
+        playlists,
     })
 }
 
+// "<dir>/<output stem>_<suffix>.jpg" for stills that weren't given a path
+fn still_path(output_path: &str, suffix: &str) -> String {
+    let output = std::path::Path::new(output_path);
+    let stem = output.file_stem().unwrap_or_default().to_string_lossy();
+    output
+        .with_file_name(format!("{}_{}.jpg", stem, suffix))
+        .to_string_lossy()
+        .to_string()
+}
+
+async fn run_still_ffmpeg(args: &[String], label: &str, log: &JobLog) -> Result<(), ErrorEnvelope> {
+    let (success, stderr) = run_logged_ffmpeg(args, label, log).await?;
+    
+    if !success {
+        return Err(ErrorEnvelope::new(
+            "STILLS_FAILED",
+            &format!("Failed to create the {}: {}", label, stderr),
+            "Check that the output folder is writable"
+        ));
+    }
+    
+    Ok(())
+}
+
+// Pick `columns` x `rows` frames evenly spread over the export, label each with its time and tile them
+fn contact_sheet_args(sheet: &ContactSheet, source: &str, total_duration_ms: u64, path: &str) -> Vec<String> {
+    let count = sheet.columns * sheet.rows;
+    let total_sec = total_duration_ms as f64 / 1000.0;
+    // Each frame comes from the middle of its share of the export, so the first isn't always black
+    let start_sec = total_sec / count as f64 / 2.0;
+    
+    let mut label = vec![
+        format!("text={}", filter_graph_value("%{pts:hms}")),
+        format!("fontsize={}", (sheet.thumbnail_width / 14).max(10)),
+        "fontcolor=white".to_string(),
+        "box=1".to_string(),
+        "boxcolor=black@0.6".to_string(),
+        "boxborderw=4".to_string(),
+        "x=6".to_string(),
+        "y=h-th-6".to_string(),
+    ];
+    if let Some(font_file) = &sheet.font_file {
+        label.insert(0, format!("fontfile={}", filter_graph_value(font_file)));
+    }
+    
+    let mut graph = FilterGraph::new();
+    graph.add_input(&["-i", source]);
+    let tiled = graph.label("sheet");
+    graph.push(format!(
+        "[{}]fps={}/{:.3}:start_time={:.3},scale={}:-2,drawtext={},tile={}x{}:padding=4:margin=4[{}]",
+        graph.video,
+        count,
+        total_sec,
+        start_sec,
+        sheet.thumbnail_width,
+        label.join(":"),
+        sheet.columns,
+        sheet.rows,
+        tiled
+    ));
+    graph.video = tiled;
+    
+    let mut args = graph.into_video_args();
+    args.extend([
+        "-frames:v".to_string(),
+        "1".to_string(),
+        "-q:v".to_string(),
+        "2".to_string(),
+        "-y".to_string(),
+        path.to_string(),
+    ]);
+    args
+}
+
+// Final paths of the poster frame and contact sheet, when they were asked for
+fn still_paths(job: &ExportJob, output_path: &str) -> (Option<String>, Option<String>) {
+    let poster = job
+        .request
+        .poster_frame
+        .as_ref()
+        .map(|poster| poster.path.clone().unwrap_or_else(|| still_path(output_path, "poster")));
+    let contact_sheet = job
+        .request
+        .contact_sheet
+        .as_ref()
+        .map(|sheet| sheet.path.clone().unwrap_or_else(|| still_path(output_path, "contact_sheet")));
+    (poster, contact_sheet)
+}
+
+// Render the poster frame and contact sheet from the file at `source`. Each goes to the temp
+// path of its entry in `paths`, to be moved into place along with the export.
+async fn render_stills(
+    job: &ExportJob,
+    source: &str,
+    paths: &(Option<String>, Option<String>),
+    total_duration_ms: u64,
+    log: &JobLog,
+) -> Result<(), ErrorEnvelope> {
+    if let (Some(poster), Some(path)) = (&job.request.poster_frame, &paths.0) {
+        let path = temp_output_path(path);
+        // The poster time is on the full timeline; the request has already been rebased to its range
+        let offset_ms = job.request.range.map(|r| r.start_ms).unwrap_or(0);
+        let time_ms = timeline_to_output_ms(&job.request, poster.time_ms.saturating_sub(offset_ms))
+            .min(total_duration_ms.saturating_sub(1));
+        
+        let args: Vec<String> = [
+            "-ss", &format!("{:.3}", time_ms as f64 / 1000.0),
+            "-i", source,
+            "-frames:v", "1",
+            "-q:v", "2",
+            "-y",
+            &path,
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+        
+        run_still_ffmpeg(&args, "poster frame", log).await?;
+    }
+    
+    if let (Some(sheet), Some(path)) = (&job.request.contact_sheet, &paths.1) {
+        let args = contact_sheet_args(sheet, source, total_duration_ms, &temp_output_path(path));
+        run_still_ffmpeg(&args, "contact sheet", log).await?;
+    }
+    
+    Ok(())
+}
+
+// Write the export to `output_path`: through the final graph when anything needs it, otherwise
+// by stream-copying the segments. Returns the loudness measurement when normalizing.
+async fn render_output(
+    app: &tauri::AppHandle,
+    job: Option<&ExportJob>,
+    list_file: &str,
+    output_path: &str,
+    total_duration_ms: u64,
+    log: &JobLog,
+) -> Result<Option<LoudnessMeasurement>, ErrorEnvelope> {
+    match job {
+        Some(job) if needs_final_graph(&job.request) => {
+            let work_dir = std::path::Path::new(list_file).parent().unwrap_or(std::path::Path::new("."));
+            let stage = build_final_stage(app, job, work_dir, list_file, total_duration_ms, log).await?;
+            
+            match (&job.request.target_size, &job.request.streaming) {
+                (Some(target), _) => {
+                    encode_to_target_size(app, job, &stage, output_path, total_duration_ms, target, log).await?;
+                }
+                (None, Some(streaming)) => {
+                    encode_streaming(app, job, &stage, output_path, total_duration_ms, streaming, log).await?;
+                }
+                (None, None) => {
+                    let args = final_output_args(job, &stage, output_path, None);
+                    run_final_encode(app, &args, total_duration_ms, log).await?;
+                }
+            }
+            
+            Ok(stage.loudness)
+        }
+        // Hard cuts only: segments share codec settings, so the concat demuxer can copy them
+        _ => {
+            let mut args: Vec<String> = [
+                "-f", "concat",
+                "-safe", "0",
+                "-i", list_file,
+            ]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+            
+            // Tags and chapters only touch the container, so they don't stop the copy
+            let metadata_job = job.filter(|job| has_container_metadata(&job.request));
+            if let Some(job) = metadata_job {
+                let work_dir = std::path::Path::new(list_file).parent().unwrap_or(std::path::Path::new("."));
+                let path = prepare_metadata(&job.request, work_dir, total_duration_ms, log).await?;
+                args.extend(["-f".to_string(), "ffmetadata".to_string(), "-i".to_string(), path]);
+            }
+            args.extend(["-c".to_string(), "copy".to_string()]);
+            if let Some(job) = metadata_job {
+                args.extend(metadata_args(&job.request, 1));
+            }
+            
+            if job.is_some_and(|job| job.request.web_optimized) {
+                args.extend(web_output_args(output_path));
+            }
+            args.extend(["-y".to_string(), output_path.to_string()]);
+            
+            run_final_encode(app, &args, total_duration_ms, log).await?;
+            Ok(None)
+        }
+    }
+}
+
 // Export: Step 2 - Concatenate segments with progress
 #[tauri::command]
 pub async fn export_concat(
@@ -142,73 +2395,85 @@ pub async fn export_concat(
     list_file: String,
     output_path: String,
     total_duration_ms: u64,
-) -> Result<(), ErrorEnvelope> {
-    use tokio::io::{AsyncBufReadExt, BufReader};
+    job_id: Option<String>,
+    jobs: State<'_, ExportJobs>,
+) -> Result<ExportResult, ErrorEnvelope> {
+    let job_id = job_id.map(|id| parse_job_id(&id)).transpose()?;
+    // Callers without a job still get a log of their own
+    let log_id = job_id.clone().unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let log = JobLog::open(&export_logs_dir(&app)?, &log_id).await;
+    let started = std::time::Instant::now();
     
-    // Start ffmpeg process with concat demuxer
-    let mut child = tokio::process::Command::new(get_ffmpeg_path())
-        .args([
-            "-f", "concat",
-            "-safe", "0",
-            "-i", &list_file,
-            "-c", "copy",
-            "-y",
-            &output_path,
-        ])
-        .stderr(std::process::Stdio::piped())
-        .spawn()
-        .map_err(|e| ErrorEnvelope::new(
-            "FFMPEG_ERROR",
-            &format!("Failed to start ffmpeg: {}", e),
-            "Make sure FFmpeg is installed"
-        ))?;
+    log.section(&format!("export_concat of {} to {} ({}ms)", list_file, output_path, total_duration_ms)).await;
     
-    // Read stderr for progress
-    if let Some(stderr) = child.stderr.take() {
-        let reader = BufReader::new(stderr);
-        let mut lines = reader.lines();
-        
-        while let Ok(Some(line)) = lines.next_line().await {
-            // Parse progress from ffmpeg output (time=hh:mm:ss.xx)
-            if line.contains("time=") {
-                if let Some(time_str) = line.split("time=").nth(1) {
-                    if let Some(time_part) = time_str.split_whitespace().next() {
-                        if let Some(current_ms) = parse_ffmpeg_time(time_part) {
-                            let progress = (current_ms as f32) / (total_duration_ms as f32);
-                            let progress = progress.min(1.0);
-                            
-                            let _ = app.emit_to(
-                                tauri::EventTarget::Any,
-                                "export-progress",
-                                ExportProgress {
-                                    stage: "concat".to_string(),
-                                    progress,
-                                    current_ms,
-                                    total_ms: total_duration_ms,
-                                    message: format!("Exporting... {:.0}%", progress * 100.0),
-                                }
-                            );
-                        }
-                    }
-                }
-            }
+    let job = job_id.as_ref().and_then(|id| jobs.lock().unwrap().remove(id));
+    let had_job = job.is_some();
+    let result = concat_export(&app, job, &list_file, output_path, total_duration_ms, &log).await;
+    // The segments are only needed once; a failed export is prepared again from scratch
+    if let (true, Some(id)) = (had_job, &job_id) {
+        remove_job_files(&app, id).await;
+    }
+    log_outcome(&log, "export_concat", started, result.as_ref().err()).await;
+    result
+}
+
+async fn concat_export(
+    app: &tauri::AppHandle,
+    job: Option<ExportJob>,
+    list_file: &str,
+    output_path: String,
+    total_duration_ms: u64,
+    log: &JobLog,
+) -> Result<ExportResult, ErrorEnvelope> {
+    let output_dir = match std::path::Path::new(&output_path).parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+    check_free_space(&output_dir, estimate_output_bytes(job.as_ref(), list_file, total_duration_ms).await, "The export")?;
+    
+    // Render next to the output and only replace it once the new file checks out,
+    // so a failed export never clobbers a good one
+    let streaming = job.as_ref().and_then(|j| j.request.streaming.as_ref());
+    let temp_path = temp_output_path(&output_path);
+    let paths = match &job {
+        Some(job) => still_paths(job, &output_path),
+        None => (None, None),
+    };
+    let stills: Vec<&String> = paths.0.iter().chain(&paths.1).collect();
+    let rendered = async {
+        let loudness = render_output(app, job.as_ref(), list_file, &temp_path, total_duration_ms, log).await?;
+        let verification = match streaming {
+            Some(streaming) => verify_streaming_output(&temp_path, total_duration_ms, streaming).await?,
+            None => verify_output(&temp_path, total_duration_ms, job.as_ref()).await?,
+        };
+        if let Some(job) = &job {
+            render_stills(job, &temp_path, &paths, total_duration_ms, log).await?;
         }
+        Ok::<_, ErrorEnvelope>((loudness, verification))
     }
+    .await;
     
-    // Wait for process to complete
-    let status = child.wait().await.map_err(|e| ErrorEnvelope::new(
-        "FFMPEG_ERROR",
-        &format!("FFmpeg process error: {}", e),
-        "Export may have been interrupted"
-    ))?;
+    let (loudness, verification) = match rendered {
+        Ok(result) => result,
+        Err(e) => {
+            remove_output(&temp_path).await;
+            for still in &stills {
+                remove_output(&temp_output_path(still)).await;
+            }
+            return Err(e);
+        }
+    };
+    log.write(&format!("{:#?}\n", verification)).await;
     
-    if !status.success() {
-        return Err(ErrorEnvelope::new(
-            "EXPORT_FAILED",
-            "FFmpeg export failed",
-            "Check if output path is writable and source files are valid"
-        ));
+    let size_bytes = match streaming {
+        Some(_) => dir_size(std::path::Path::new(&temp_path)),
+        None => file_size(&temp_path).await?,
+    };
+    replace_output(&temp_path, &output_path).await?;
+    for still in &stills {
+        replace_output(&temp_output_path(still), still).await?;
     }
+    let (poster_path, contact_sheet_path) = paths;
     
     // Emit completion
     let _ = app.emit_to(
@@ -223,6 +2488,330 @@ pub async fn export_concat(
         }
     );
     
-    Ok(())
+    Ok(ExportResult {
+        output_path,
+        size_bytes,
+        loudness,
+        verification,
+        log_path: log.path(),
+        poster_path,
+        contact_sheet_path,
+    })
+}
+
+// Single-clip export request for one file of a batch
+fn profile_request(profile: &ExportProfile, clip: &ExportClip, output_path: &str) -> ExportRequest {
+    ExportRequest {
+        clips: vec![ExportClip {
+            start_ms: 0,
+            ..clip.clone()
+        }],
+        output_path: output_path.to_string(),
+        width: profile.width,
+        height: profile.height,
+        gap_color: None,
+        transitions: Vec::new(),
+        video_tracks: Vec::new(),
+        audio_tracks: Vec::new(),
+        text_overlays: Vec::new(),
+        watermark: profile.watermark.clone(),
+        loudness: profile.loudness.clone(),
+        subtitles: Vec::new(),
+        target_size: profile.target_size.clone(),
+        range: None,
+        streaming: None,
+        metadata: profile.metadata.clone(),
+        chapters: Vec::new(),
+        chapters_per_clip: false,
+        web_optimized: profile.web_optimized,
+        poster_frame: None,
+        contact_sheet: None,
+    }
+}
+
+fn emit_batch_progress(app: &tauri::AppHandle, index: usize, count: usize, output_path: &str, progress: f32, message: String) {
+    let _ = app.emit_to(
+        tauri::EventTarget::Any,
+        "batch-export-progress",
+        ClipBatchProgress {
+            index,
+            count,
+            output_path: output_path.to_string(),
+            progress,
+            message,
+        }
+    );
+}
+
+// Export every clip to its own file, named from the template and encoded with the shared profile.
+// All clips are validated up front, then exported one after another; the first failure stops the batch.
+#[tauri::command]
+pub async fn export_clips_separately(
+    app: tauri::AppHandle,
+    request: ClipBatchRequest,
+    jobs: State<'_, ExportJobs>,
+) -> Result<Vec<ExportResult>, ErrorEnvelope> {
+    if request.clips.is_empty() {
+        return Err(ErrorEnvelope::new(
+            "EMPTY_TIMELINE",
+            "There are no clips to export",
+            "Add at least one clip to the timeline before exporting"
+        ));
+    }
+    
+    validate_filename_template(&request.filename_template)
+        .map_err(|e| ErrorEnvelope::new(
+            "INVALID_TEMPLATE",
+            &e,
+            "Use {index}, {asset_name}, {in} and {out} in the filename template"
+        ))?;
+    
+    prune_stale_jobs(&app, &jobs).await;
+    prune_old_files(&export_logs_dir(&app)?, "log", LOG_RETENTION).await;
+    let count = request.clips.len();
+    let output_dir = std::path::Path::new(&request.output_dir);
+    let mut output_paths: Vec<String> = Vec::new();
+    
+    for (i, clip) in request.clips.iter().enumerate() {
+        let name = render_filename_template(&request.filename_template, i, count, clip);
+        let path = output_dir.join(&name).to_string_lossy().to_string();
+        
+        // Case-insensitive file systems would have the later file overwrite the earlier one
+        if let Some(earlier) = output_paths.iter().position(|p| p.to_lowercase() == path.to_lowercase()) {
+            return Err(ErrorEnvelope::new(
+                "INVALID_TEMPLATE",
+                &format!("Clips {} and {} would both be written to {}", earlier + 1, i + 1, name),
+                "Add {index} to the filename template so every file gets its own name"
+            ));
+        }
+        output_paths.push(path);
+    }
+    
+    // Check every clip before the first file is written, so a bad clip can't leave half a batch behind
+    let clip_requests: Vec<ExportRequest> = request
+        .clips
+        .iter()
+        .zip(&output_paths)
+        .map(|(clip, output_path)| profile_request(&request.profile, clip, output_path))
+        .collect();
+    for (i, (clip_request, clip)) in clip_requests.iter().zip(&request.clips).enumerate() {
+        validate_export_request(clip_request).await.map_err(|e| ErrorEnvelope::new(
+            &e.code,
+            &format!("Clip {} of {} ({}): {}", i + 1, count, clip.asset_path, e.message),
+            &e.hint
+        ))?;
+    }
+    
+    tokio::fs::create_dir_all(output_dir)
+        .await
+        .map_err(|e| ErrorEnvelope::new(
+            "DIR_CREATE_ERROR",
+            &format!("Failed to create output folder: {}", e),
+            "Check that the output location is writable"
+        ))?;
+    
+    let total_ms: u64 = request.clips.iter().map(|c| c.duration_ms()).sum::<u64>().max(1);
+    let mut done_ms = 0;
+    let mut results = Vec::new();
+    
+    for (i, ((clip, output_path), clip_request)) in request.clips.iter().zip(&output_paths).zip(clip_requests).enumerate() {
+        // Each file's share of the batch is split evenly between preparing its segment and the final encode
+        let file_start = done_ms as f32 / total_ms as f32;
+        let file_share = clip.duration_ms() as f32 / total_ms as f32;
+        emit_batch_progress(
+            &app,
+            i,
+            count,
+            output_path,
+            file_start,
+            format!("Preparing clip {} of {}", i + 1, count)
+        );
+        
+        let job_id = uuid::Uuid::new_v4().to_string();
+        let log = JobLog::open(&export_logs_dir(&app)?, &job_id).await;
+        let started = std::time::Instant::now();
+        
+        log.section(&format!("export_clips_separately, clip {} of {}, job {}", i + 1, count, job_id)).await;
+        log.write(&format!("{}\n", serde_json::to_string_pretty(&clip_request).unwrap_or_default())).await;
+        
+        let result = async {
+            let prepared = prepare_export(&app, clip_request, job_id.clone(), &jobs, &log).await?;
+            emit_batch_progress(
+                &app,
+                i,
+                count,
+                output_path,
+                file_start + file_share / 2.0,
+                format!("Exporting clip {} of {}", i + 1, count)
+            );
+            
+            // Fold the file's own export-progress into the batch total. Loudness and size passes each
+            // start again from zero, so only ever move forward.
+            let file_progress = std::sync::Arc::new(std::sync::Mutex::new(0.0f32));
+            let forward = {
+                let app_handle = app.clone();
+                let output_path = output_path.clone();
+                app.listen_any("export-progress", move |event| {
+                    let Ok(progress) = serde_json::from_str::<serde_json::Value>(event.payload()) else {
+                        return;
+                    };
+                    let Some(value) = progress["progress"].as_f64() else {
+                        return;
+                    };
+                    let mut file_progress = file_progress.lock().unwrap();
+                    *file_progress = file_progress.max(value as f32);
+                    emit_batch_progress(
+                        &app_handle,
+                        i,
+                        count,
+                        &output_path,
+                        file_start + file_share * (0.5 + *file_progress / 2.0),
+                        format!("Exporting clip {} of {}", i + 1, count)
+                    );
+                })
+            };
+            
+            let job = jobs.lock().unwrap().remove(&job_id);
+            let result = concat_export(&app, job, &prepared.list_file, output_path.clone(), prepared.total_duration_ms, &log).await;
+            app.unlisten(forward);
+            result
+        }
+        .await;
+        remove_job_files(&app, &job_id).await;
+        log_outcome(&log, "export_clips_separately", started, result.as_ref().err()).await;
+        
+        let result = result.map_err(|e| ErrorEnvelope::new(
+            &e.code,
+            &format!("Clip {} of {} ({}): {}", i + 1, count, clip.asset_path, e.message),
+            &e.hint
+        ))?;
+        
+        done_ms += clip.duration_ms();
+        results.push(result);
+    }
+    
+    emit_batch_progress(
+        &app,
+        count.saturating_sub(1),
+        count,
+        &request.output_dir,
+        1.0,
+        format!("Exported {} files", count)
+    );
+    
+    Ok(results)
+}
+
+// Path of an export's log, for attaching to bug reports. Without a job id, the most recent
+// log is returned, which covers exports whose preparation failed before an id was handed out.
+#[tauri::command]
+pub async fn export_log_path(app: tauri::AppHandle, job_id: Option<String>) -> Result<String, ErrorEnvelope> {
+    let dir = export_logs_dir(&app)?;
+    
+    let path = match job_id {
+        Some(job_id) => Some(dir.join(format!("{}.log", parse_job_id(&job_id)?))).filter(|p| p.is_file()),
+        None => std::fs::read_dir(&dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "log"))
+            .max_by_key(|entry| entry.metadata().and_then(|m| m.modified()).ok())
+            .map(|entry| entry.path()),
+    };
+    
+    path.map(|p| p.to_string_lossy().to_string()).ok_or_else(|| ErrorEnvelope::new(
+        "LOG_NOT_FOUND",
+        "No export log was found",
+        "Logs are written once an export has been started"
+    ))
 }
 
+// Cached preview frames are kept this long after they were rendered
+const PREVIEW_RETENTION: std::time::Duration = std::time::Duration::from_secs(24 * 60 * 60);
+
+// Render a single frame of a clip with its crop, rotation and color applied,
+// so adjustments can be checked before a full export
+#[tauri::command]
+pub async fn preview_clip_frame(
+    app: tauri::AppHandle,
+    clip: ExportClip,
+    time_ms: u64,
+) -> Result<String, ErrorEnvelope> {
+    if !std::path::Path::new(&clip.asset_path).exists() {
+        return Err(ErrorEnvelope::new(
+            "FILE_NOT_FOUND",
+            &format!("File not found: {}", clip.asset_path),
+            "Check that the file path is correct"
+        ));
+    }
+    
+    // The same checks as an export, so a bad crop or rotation isn't silently ignored or left to ffmpeg
+    let source = probe_media(clip.asset_path.clone()).await?;
+    let mut problems = color_problems(&clip);
+    problems.extend(transform_problems(&clip, &source));
+    if !problems.is_empty() {
+        return Err(ErrorEnvelope::new(
+            "INVALID_CLIPS",
+            &problems.join("\n"),
+            "Adjust the clip's crop, rotation or color settings and try again"
+        ));
+    }
+    
+    let app_data = get_app_data_dir(&app)?;
+    let preview_dir = app_data.join("previews");
+    // Every settings change caches another frame, so clear out old ones as logs are
+    prune_old_files(&preview_dir, "jpg", PREVIEW_RETENTION).await;
+    
+    tokio::fs::create_dir_all(&preview_dir)
+        .await
+        .map_err(|e| ErrorEnvelope::new(
+            "DIR_CREATE_ERROR",
+            &format!("Failed to create previews directory: {}", e),
+            "Check application permissions"
+        ))?;
+    
+    // Same settings give the same file, so repeated previews are free
+    let hash = format!("{:x}", md5::compute(format!("{:?}@{}", clip, time_ms).as_bytes()));
+    let preview_path = preview_dir.join(format!("{}.jpg", hash));
+    
+    if preview_path.exists() {
+        return Ok(preview_path.to_string_lossy().to_string());
+    }
+    
+    let time_sec = format!("{:.3}", time_ms as f64 / 1000.0);
+    let mut graph = FilterGraph::new();
+    graph.add_input(&["-ss", &time_sec, "-i", &clip.asset_path]);
+    apply_transform(&mut graph, &clip);
+    apply_color(&mut graph, &clip);
+    
+    let mut args = graph.into_video_args();
+    args.extend([
+        "-frames:v".to_string(),
+        "1".to_string(),
+        "-q:v".to_string(),
+        "2".to_string(),
+        "-y".to_string(),
+        preview_path.to_string_lossy().to_string(),
+    ]);
+    
+    let output = tokio::process::Command::new(get_ffmpeg_path())
+        .args(&args)
+        .output()
+        .await
+        .map_err(|e| ErrorEnvelope::new(
+            "FFMPEG_ERROR",
+            &format!("Failed to run ffmpeg: {}", e),
+            "Make sure FFmpeg is installed: brew install ffmpeg"
+        ))?;
+    
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(ErrorEnvelope::new(
+            "PREVIEW_FAILED",
+            &format!("ffmpeg failed to render preview frame: {}", stderr),
+            "Check the clip's LUT file and that the time is within the source"
+        ));
+    }
+    
+    Ok(preview_path.to_string_lossy().to_string())
+}
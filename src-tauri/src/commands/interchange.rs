@@ -0,0 +1,238 @@
+use crate::commands::export::clip_timing_problems;
+use crate::commands::media::probe_media;
+use crate::interchange::*;
+use crate::types::*;
+
+// Write the main-track clips as an EDL, FCPXML or OTIO file for other editors.
+// Each source is probed for its frame rate so timecodes are frame-accurate.
+#[tauri::command]
+pub async fn export_interchange(
+    clips: Vec<ExportClip>,
+    format: InterchangeFormat,
+    title: String,
+    output_path: String,
+) -> Result<String, ErrorEnvelope> {
+    if clips.is_empty() {
+        return Err(ErrorEnvelope::new(
+            "EMPTY_TIMELINE",
+            "There are no clips to export",
+            "Add at least one clip to the timeline before exporting"
+        ));
+    }
+    
+    let mut clips = clips;
+    clips.sort_by_key(|clip| clip.start_ms);
+    
+    let mut sources = Vec::new();
+    let mut problems = clip_timing_problems(&clips);
+    if !problems.is_empty() {
+        return Err(ErrorEnvelope::new(
+            "INVALID_CLIPS",
+            &problems.join("\n"),
+            "Fix the listed clips and try again"
+        ));
+    }
+    
+    for (i, clip) in clips.iter().enumerate() {
+        match probe_media(clip.asset_path.clone()).await {
+            Ok(metadata) if clip.freeze_ms.is_some() && clip.in_ms >= metadata.duration_ms => problems.push(format!(
+                "Clip {}: freeze frame at {}ms is past the end of {} ({}ms)",
+                i, clip.in_ms, clip.asset_path, metadata.duration_ms
+            )),
+            Ok(metadata) if clip.freeze_ms.is_none() && clip.out_ms > metadata.duration_ms => problems.push(format!(
+                "Clip {}: out point ({}ms) is past the end of {} ({}ms)",
+                i, clip.out_ms, clip.asset_path, metadata.duration_ms
+            )),
+            Ok(metadata) => sources.push(InterchangeClip {
+                path: clip.asset_path.clone(),
+                source_in_ms: clip.in_ms,
+                source_out_ms: clip.out_ms,
+                start_ms: clip.start_ms,
+                duration_ms: clip.duration_ms(),
+                speed: clip.speed,
+                reverse: clip.reverse,
+                freeze: clip.freeze_ms.is_some(),
+                fps: metadata.fps.unwrap_or(30.0),
+                width: metadata.width,
+                height: metadata.height,
+                has_audio: metadata.has_audio,
+                source_duration_ms: metadata.duration_ms,
+            }),
+            Err(e) => problems.push(format!("Clip {}: {}", i, e.message)),
+        }
+    }
+    
+    if !problems.is_empty() {
+        return Err(ErrorEnvelope::new(
+            "INVALID_CLIPS",
+            &problems.join("\n"),
+            "Relink or trim the listed clips and try again"
+        ));
+    }
+    
+    // The timeline runs at the first clip's rate, as the export does
+    let timeline_fps = sources[0].fps;
+    let content = match format {
+        InterchangeFormat::Edl => to_edl(&sources, &title, timeline_fps),
+        InterchangeFormat::Fcpxml => to_fcpxml(&sources, &title, timeline_fps),
+        InterchangeFormat::Otio => to_otio(&sources, &title, timeline_fps),
+    };
+    
+    tokio::fs::write(&output_path, content)
+        .await
+        .map_err(|e| ErrorEnvelope::new(
+            "FILE_WRITE_ERROR",
+            &format!("Failed to write {}: {}", output_path, e),
+            "Check that the output location is writable"
+        ))?;
+    
+    Ok(output_path)
+}
+
+// How deep below each search path media files are looked for
+const SEARCH_DEPTH: usize = 4;
+
+fn collect_media_files(dir: &std::path::Path, depth: usize, files: &mut Vec<std::path::PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            if depth > 0 {
+                collect_media_files(&path, depth - 1, files);
+            }
+        } else {
+            files.push(path);
+        }
+    }
+}
+
+// Find an edit's media: the recorded path if it still exists, otherwise a file under the
+// search paths named like the recorded file, the clip or the reel (with or without extension)
+fn resolve_media(event: &InterchangeEvent, files: &[std::path::PathBuf]) -> Option<String> {
+    if let Some(source_file) = &event.source_file {
+        if std::path::Path::new(source_file).is_file() {
+            return Some(source_file.clone());
+        }
+    }
+    
+    let recorded_name = event
+        .source_file
+        .as_ref()
+        .and_then(|f| std::path::Path::new(f).file_name())
+        .map(|n| n.to_string_lossy().to_string());
+    let candidates: Vec<String> = [recorded_name, event.clip_name.clone(), Some(event.reel.clone())]
+        .into_iter()
+        .flatten()
+        .filter(|c| !c.is_empty())
+        .map(|c| c.to_lowercase())
+        .collect();
+    
+    for candidate in &candidates {
+        let found = files
+            .iter()
+            .find(|f| f.file_name().is_some_and(|n| n.to_string_lossy().to_lowercase() == *candidate))
+            .or_else(|| files.iter().find(|f| f.file_stem().is_some_and(|n| n.to_string_lossy().to_lowercase() == *candidate)));
+        if let Some(found) = found {
+            return Some(found.to_string_lossy().to_string());
+        }
+    }
+    None
+}
+
+// Read a CMX3600 EDL or OTIO file into export clips, finding media by recorded path or
+// under `search_paths`. EDL record timecodes are read at `fps` (30 if not given).
+#[tauri::command]
+pub async fn import_interchange(
+    path: String,
+    search_paths: Vec<String>,
+    fps: Option<f64>,
+) -> Result<InterchangeImport, ErrorEnvelope> {
+    let content = tokio::fs::read_to_string(&path)
+        .await
+        .map_err(|e| ErrorEnvelope::new(
+            "FILE_READ_ERROR",
+            &format!("Failed to read {}: {}", path, e),
+            "Make sure the file exists and is a text EDL or OTIO file"
+        ))?;
+    
+    // OTIO is JSON; anything else is treated as an EDL
+    let events = if content.trim_start().starts_with('{') {
+        parse_otio(&content)
+    } else {
+        parse_edl(&content, fps.unwrap_or(30.0))
+    }
+    .map_err(|e| ErrorEnvelope::new(
+        "INVALID_INTERCHANGE",
+        &format!("{}: {}", path, e),
+        "Check that the file is a CMX3600 EDL or an OpenTimelineIO timeline"
+    ))?;
+    
+    let mut files = Vec::new();
+    for search_path in &search_paths {
+        collect_media_files(std::path::Path::new(search_path), SEARCH_DEPTH, &mut files);
+    }
+    
+    let mut clips = Vec::new();
+    let mut unresolved = Vec::new();
+    
+    for (i, event) in events.iter().enumerate() {
+        let resolved = match resolve_media(event, &files) {
+            Some(asset_path) => probe_media(asset_path.clone())
+                .await
+                .map(|metadata| (asset_path, metadata))
+                .map_err(|e| e.message),
+            None => Err("No matching file in the search paths".to_string()),
+        };
+        
+        let in_ms = resolved.as_ref().ok().and_then(|(_, metadata)| event.source_in.to_ms(metadata.fps.unwrap_or(30.0)));
+        let (asset_path, in_ms) = match (resolved, in_ms) {
+            (Ok((asset_path, _)), Some(in_ms)) => (asset_path, in_ms),
+            (result, _) => {
+                unresolved.push(UnresolvedMedia {
+                    event: i + 1,
+                    reel: event.reel.clone(),
+                    clip_name: event.clip_name.clone(),
+                    source_file: event.source_file.clone(),
+                    start_ms: event.record_in_ms,
+                    duration_ms: event.record_duration_ms,
+                    reason: result.err().unwrap_or_else(|| "Invalid source timecode".to_string()),
+                });
+                continue;
+            }
+        };
+        
+        let freeze = event.speed == 0.0;
+        let speed = if freeze { 1.0 } else { event.speed.abs() };
+        let out_ms = if freeze {
+            in_ms
+        } else {
+            in_ms + (event.record_duration_ms as f64 * speed).round() as u64
+        };
+        
+        clips.push(ExportClip {
+            asset_path,
+            in_ms,
+            out_ms,
+            start_ms: event.record_in_ms,
+            speed,
+            reverse: event.speed < 0.0,
+            freeze_ms: freeze.then_some(event.record_duration_ms),
+            gain_db: 0.0,
+            video_fade_in_ms: 0,
+            video_fade_out_ms: 0,
+            audio_fade_in_ms: 0,
+            audio_fade_out_ms: 0,
+            declick: false,
+            crop: None,
+            rotation: 0,
+            flip_horizontal: false,
+            flip_vertical: false,
+            color: None,
+            lut_path: None,
+        });
+    }
+    
+    Ok(InterchangeImport { clips, unresolved })
+}
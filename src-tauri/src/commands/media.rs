@@ -32,11 +32,10 @@ pub async fn check_ffmpeg() -> Result<bool, ErrorEnvelope> {
     }
 }
 
-// Task 1.2: Probe media file
-#[tauri::command]
-pub async fn probe_media(path: String) -> Result<MediaMetadata, ErrorEnvelope> {
+// Run ffprobe on a file and parse its stream/format JSON
+pub async fn run_ffprobe(path: &str) -> Result<FFprobeOutput, ErrorEnvelope> {
     // Check if file exists
-    if !std::path::Path::new(&path).exists() {
+    if !std::path::Path::new(path).exists() {
         return Err(ErrorEnvelope::new(
             "FILE_NOT_FOUND",
             &format!("File not found: {}", path),
@@ -51,7 +50,7 @@ pub async fn probe_media(path: String) -> Result<MediaMetadata, ErrorEnvelope> {
             "-show_streams",
             "-show_format",
             "-print_format", "json",
-            &path
+            path
         ])
         .output()
         .await
@@ -72,12 +71,40 @@ pub async fn probe_media(path: String) -> Result<MediaMetadata, ErrorEnvelope> {
     
     // Parse JSON output
     let stdout = String::from_utf8_lossy(&output.stdout);
-    let probe_data: FFprobeOutput = serde_json::from_str(&stdout)
+    serde_json::from_str(&stdout)
         .map_err(|e| ErrorEnvelope::new(
             "PARSE_ERROR",
             &format!("Failed to parse ffprobe output: {}", e),
             "The file may be corrupted"
-        ))?;
+        ))
+}
+
+// Probe a file used only for its audio (music, voiceover); returns its duration in ms
+pub async fn probe_audio(path: &str) -> Result<u64, ErrorEnvelope> {
+    let probe_data = run_ffprobe(path).await?;
+    
+    if !probe_data.streams.iter().any(|s| s.codec_type == "audio") {
+        return Err(ErrorEnvelope::new(
+            "NO_AUDIO_STREAM",
+            &format!("No audio stream found in {}", path),
+            "Make sure the file contains audio"
+        ));
+    }
+    
+    probe_data.format.duration
+        .and_then(|d| d.parse::<f64>().ok())
+        .map(|d| (d * 1000.0) as u64)
+        .ok_or_else(|| ErrorEnvelope::new(
+            "NO_DURATION",
+            "Could not determine audio duration",
+            "The file may be corrupted"
+        ))
+}
+
+// Task 1.2: Probe media file
+#[tauri::command]
+pub async fn probe_media(path: String) -> Result<MediaMetadata, ErrorEnvelope> {
+    let probe_data = run_ffprobe(&path).await?;
     
     // Find video stream
     let video_stream = probe_data.streams.iter()
@@ -98,18 +125,26 @@ pub async fn probe_media(path: String) -> Result<MediaMetadata, ErrorEnvelope> {
             "The file may be corrupted"
         ))?;
     
-    let width = video_stream.width.ok_or_else(|| ErrorEnvelope::new(
+    let coded_width = video_stream.width.ok_or_else(|| ErrorEnvelope::new(
         "NO_WIDTH",
         "Could not determine video width",
         "The file may be corrupted"
     ))?;
     
-    let height = video_stream.height.ok_or_else(|| ErrorEnvelope::new(
+    let coded_height = video_stream.height.ok_or_else(|| ErrorEnvelope::new(
         "NO_HEIGHT",
         "Could not determine video height",
         "The file may be corrupted"
     ))?;
     
+    // Report the upright size so portrait phone footage isn't shown sideways
+    let rotation = parse_stream_rotation(video_stream);
+    let (width, height) = if rotation % 180 == 90 {
+        (coded_height, coded_width)
+    } else {
+        (coded_width, coded_height)
+    };
+    
     let fps = video_stream.r_frame_rate
         .as_ref()
         .and_then(|r| parse_frame_rate(r));
@@ -117,12 +152,16 @@ pub async fn probe_media(path: String) -> Result<MediaMetadata, ErrorEnvelope> {
     let size_bytes = probe_data.format.size
         .and_then(|s| s.parse::<u64>().ok());
     
+    let has_audio = probe_data.streams.iter().any(|s| s.codec_type == "audio");
+    
     Ok(MediaMetadata {
         duration_ms,
         width,
         height,
         fps,
         size_bytes,
+        has_audio,
+        rotation,
     })
 }
 
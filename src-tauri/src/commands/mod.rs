@@ -1,11 +1,13 @@
 pub mod export;
 pub mod files;
+pub mod interchange;
 pub mod media;
 pub mod recording;
 
 // Re-export all commands for easy registration in lib.rs
 pub use export::*;
 pub use files::*;
+pub use interchange::*;
 pub use media::*;
 pub use recording::*;
 
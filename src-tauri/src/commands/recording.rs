@@ -321,7 +321,10 @@ pub async fn start_combined_recording(
         // [0:v] = screen video, [1:v] = webcam video
         // overlay=W-w-20:H-h-20 = position webcam at bottom-right with 20px padding
         // scale=320:240 = resize webcam to 320x240
-        let filter_complex = "[1:v]scale=320:240[pip];[0:v][pip]overlay=W-w-20:H-h-20[v]";
+        let filter_complex = format!(
+            "[1:v]scale=320:240[pip];{}",
+            crate::ffmpeg::overlay_filter("0:v", "pip", "W-w-20", "H-h-20", None, "v")
+        );
         
         let child = tokio::process::Command::new("ffmpeg")
             .args(&[
@@ -331,7 +334,7 @@ pub async fn start_combined_recording(
                 "-f", "avfoundation",
                 "-framerate", "30",
                 "-i", &webcam_input,
-                "-filter_complex", filter_complex,
+                "-filter_complex", &filter_complex,
                 "-map", "[v]",      // Map the filtered video output
                 "-map", "0:a?",     // Map audio from first input (screen), ? makes it optional
                 "-c:v", "libx264",
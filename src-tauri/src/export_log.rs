@@ -0,0 +1,111 @@
+// Per-job export log: the request and every ffmpeg run with its timing, exit status and stderr
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+
+pub struct JobLog {
+    path: PathBuf,
+}
+
+/// Wall-clock time of day in UTC, e.g. "14:03:22.511"
+fn utc_time() -> String {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default();
+    let secs = now.as_secs() % 86_400;
+    format!(
+        "{:02}:{:02}:{:02}.{:03}",
+        secs / 3600,
+        (secs / 60) % 60,
+        secs % 60,
+        now.subsec_millis()
+    )
+}
+
+/// Command line as it could be pasted into a shell, quoting arguments with spaces or specials
+pub fn format_argv(program: &str, args: &[String]) -> String {
+    std::iter::once(program)
+        .chain(args.iter().map(String::as_str))
+        .map(|arg| {
+            if !arg.is_empty() && arg.chars().all(|c| c.is_ascii_alphanumeric() || "-_./:=@%+,".contains(c)) {
+                arg.to_string()
+            } else {
+                format!("'{}'", arg.replace('\'', "'\\''"))
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Delete the files ending in `.{extension}` in `dir` that were last written more than `max_age` ago
+pub async fn prune_old_files(dir: &Path, extension: &str, max_age: Duration) {
+    let Ok(mut entries) = tokio::fs::read_dir(dir).await else {
+        return;
+    };
+    
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let path = entry.path();
+        if path.extension().is_none_or(|ext| ext != extension) {
+            continue;
+        }
+        let age = entry
+            .metadata()
+            .await
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|modified| modified.elapsed().ok());
+        if age.is_some_and(|age| age > max_age) {
+            let _ = tokio::fs::remove_file(path).await;
+        }
+    }
+}
+
+impl JobLog {
+    /// Log for `job_id` in `dir`. Both export stages write to the same file, so it is appended to.
+    pub async fn open(dir: &Path, job_id: &str) -> Self {
+        let _ = tokio::fs::create_dir_all(dir).await;
+        Self {
+            path: dir.join(format!("{}.log", job_id)),
+        }
+    }
+    
+    pub fn path(&self) -> String {
+        self.path.to_string_lossy().to_string()
+    }
+    
+    /// Append text; a log that can't be written never fails the export
+    pub async fn write(&self, text: &str) {
+        let file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await;
+        if let Ok(mut file) = file {
+            let _ = file.write_all(text.as_bytes()).await;
+        }
+    }
+    
+    /// Start a titled section, stamped with the time
+    pub async fn section(&self, title: &str) {
+        self.write(&format!("\n=== {} UTC  {} ===\n", utc_time(), title)).await;
+    }
+    
+    /// Record one ffmpeg run. `exit` is None when the process couldn't be started or waited on.
+    pub async fn command(&self, label: &str, program: &str, args: &[String], exit: Option<i32>, elapsed: Duration, stderr: &str) {
+        let exit = match exit {
+            Some(code) => code.to_string(),
+            None => "none".to_string(),
+        };
+        self.write(&format!(
+            "\n--- {} UTC  {} ---\n{}\nexit code: {}, took {:.2}s\n{}\n",
+            utc_time(),
+            label,
+            format_argv(program, args),
+            exit,
+            elapsed.as_secs_f64(),
+            stderr.trim_end()
+        ))
+        .await;
+    }
+}
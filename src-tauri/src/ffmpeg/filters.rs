@@ -0,0 +1,653 @@
+use crate::types::*;
+
+// Backslash-escape every character in `special`
+fn escape_chars(value: &str, special: &[char]) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if special.contains(&c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Escape a value for use as a filter option (`key=value`), the first of ffmpeg's two escaping levels
+pub fn escape_filter_option(value: &str) -> String {
+    escape_chars(value, &['\\', '\'', ':'])
+}
+
+/// Escape a filter description for embedding in a -filter_complex graph, the second escaping level
+pub fn escape_filter_graph(value: &str) -> String {
+    escape_chars(value, &['\\', '\'', ',', ';', '[', ']'])
+}
+
+/// Escape arbitrary user text (or a path) for use as an option value inside a filter graph
+pub fn filter_graph_value(value: &str) -> String {
+    escape_filter_graph(&escape_filter_option(value))
+}
+
+/// Incrementally built -filter_complex graph together with the inputs it reads.
+/// `video` and `audio` hold the label of the current output of each chain.
+#[derive(Clone)]
+pub struct FilterGraph {
+    input_args: Vec<String>,
+    input_count: usize,
+    filters: Vec<String>,
+    label_count: usize,
+    pub video: String,
+    pub audio: String,
+}
+
+impl FilterGraph {
+    pub fn new() -> Self {
+        Self {
+            input_args: Vec::new(),
+            input_count: 0,
+            filters: Vec::new(),
+            label_count: 0,
+            video: "0:v:0".to_string(),
+            audio: "0:a:0".to_string(),
+        }
+    }
+    
+    /// Add an input; `args` are its input options ending with "-i <path>". Returns the input index.
+    pub fn add_input(&mut self, args: &[&str]) -> usize {
+        self.input_args.extend(args.iter().map(|a| a.to_string()));
+        self.input_count += 1;
+        self.input_count - 1
+    }
+    
+    pub fn push(&mut self, filter: String) {
+        self.filters.push(filter);
+    }
+    
+    /// Fresh, unique pad label
+    pub fn label(&mut self, prefix: &str) -> String {
+        self.label_count += 1;
+        format!("{}{}", prefix, self.label_count)
+    }
+    
+    /// Input arguments, the graph and the -map options for the current video and audio outputs
+    pub fn into_args(self) -> Vec<String> {
+        self.build_args(true, true)
+    }
+    
+    /// Like into_args, but only maps the audio; the video chain is terminated in a nullsink
+    pub fn into_audio_args(self) -> Vec<String> {
+        self.build_args(false, true)
+    }
+    
+    /// Like into_args, but only maps the video; the audio chain is terminated in an anullsink
+    pub fn into_video_args(self) -> Vec<String> {
+        self.build_args(true, false)
+    }
+    
+    /// Input arguments and the graph, mapping the given filter outputs in order instead of the
+    /// current video and audio. The caller must have consumed `video` and `audio` in the graph.
+    pub fn into_mapped_args(self, outputs: &[String]) -> Vec<String> {
+        let mut args = self.input_args;
+        if !self.filters.is_empty() {
+            args.extend(["-filter_complex".to_string(), self.filters.join(";")]);
+        }
+        for output in outputs {
+            args.extend(["-map".to_string(), format!("[{}]", output)]);
+        }
+        args
+    }
+    
+    fn build_args(mut self, video: bool, audio: bool) -> Vec<String> {
+        // Raw input streams ("0:v:0") are left out or mapped as-is, filter outputs need brackets
+        let is_input_stream = |label: &str| label.contains(':');
+        
+        if !video && !is_input_stream(&self.video) {
+            self.filters.push(format!("[{}]nullsink", self.video));
+        }
+        if !audio && !is_input_stream(&self.audio) {
+            self.filters.push(format!("[{}]anullsink", self.audio));
+        }
+        
+        let mut args = self.input_args;
+        if !self.filters.is_empty() {
+            args.extend(["-filter_complex".to_string(), self.filters.join(";")]);
+        }
+        
+        let mapped = [(video, &self.video), (audio, &self.audio)];
+        for (_, label) in mapped.iter().filter(|(wanted, _)| *wanted) {
+            let spec = if is_input_stream(label) {
+                label.to_string()
+            } else {
+                format!("[{}]", label)
+            };
+            args.extend(["-map".to_string(), spec]);
+        }
+        args
+    }
+}
+
+/// Overlay [layer] on [base] with its top-left corner at (x, y). Expressions may use W/H/w/h, e.g. "W-w-20".
+/// With `enable` set, the layer only shows between those timeline seconds and the base passes through otherwise.
+pub fn overlay_filter(base: &str, layer: &str, x: &str, y: &str, enable: Option<(f64, f64)>, output: &str) -> String {
+    match enable {
+        Some((start, end)) => format!(
+            "[{base}][{layer}]overlay={x}:{y}:eof_action=pass:enable='between(t,{:.3},{:.3})'[{output}]",
+            start, end
+        ),
+        None => format!("[{base}][{layer}]overlay={x}:{y}[{output}]"),
+    }
+}
+
+/// Composite overlay track clips onto the graph's video in z-order, at their timeline positions.
+/// Each clip becomes a trimmed input; its audio is ignored.
+pub fn composite_track_clips(graph: &mut FilterGraph, tracks: &[ExportVideoTrack]) {
+    let mut layers: Vec<&TrackClip> = tracks.iter().flat_map(|t| t.clips.iter()).collect();
+    layers.sort_by_key(|clip| clip.z_order);
+    
+    for clip in layers {
+        let in_sec = format!("{:.3}", clip.in_ms as f64 / 1000.0);
+        let duration_sec = format!("{:.3}", clip.out_ms.saturating_sub(clip.in_ms) as f64 / 1000.0);
+        let input = graph.add_input(&["-ss", &in_sec, "-t", &duration_sec, "-i", &clip.asset_path]);
+        
+        let start_sec = clip.start_ms as f64 / 1000.0;
+        let end_sec = start_sec + clip.out_ms.saturating_sub(clip.in_ms) as f64 / 1000.0;
+        
+        // Shift the layer to its timeline position so overlay lines the frames up
+        let layer = graph.label("layer");
+        graph.push(format!(
+            "[{input}:v]scale=iw*{}:ih*{},format=rgba,colorchannelmixer=aa={},setpts=PTS-STARTPTS+{:.3}/TB[{layer}]",
+            clip.scale, clip.scale, clip.opacity, start_sec
+        ));
+        
+        let output = graph.label("composite");
+        let base = graph.video.clone();
+        graph.push(overlay_filter(
+            &base,
+            &layer,
+            &clip.x.to_string(),
+            &clip.y.to_string(),
+            Some((start_sec, end_sec)),
+            &output,
+        ));
+        graph.video = output;
+    }
+}
+
+/// Check that a color is safe to embed in a filter graph.
+/// Accepts ffmpeg color names ("black"), "#RRGGBB" / "0xRRGGBB" and an optional "@alpha" suffix.
+pub fn is_valid_color(color: &str) -> bool {
+    !color.is_empty()
+        && color
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '#' || c == '@' || c == '.')
+}
+
+// ffmpeg xfade transition implementing each transition kind
+fn xfade_transition(kind: TransitionKind) -> &'static str {
+    match kind {
+        TransitionKind::Dissolve => "fade",
+        TransitionKind::FadeBlack => "fadeblack",
+        TransitionKind::Wipe => "wipeleft",
+        TransitionKind::Slide => "slideleft",
+    }
+}
+
+fn acrossfade_curve(curve: AudioCurve) -> &'static str {
+    match curve {
+        AudioCurve::Tri => "tri",
+        AudioCurve::Qsin => "qsin",
+        AudioCurve::Hsin => "hsin",
+        AudioCurve::Esin => "esin",
+        AudioCurve::Log => "log",
+        AudioCurve::Exp => "exp",
+    }
+}
+
+/// Build a filter graph joining prepared segments (ffmpeg inputs 0..n, in order) into [vout] and [aout].
+/// Segments without a transition are hard cut with the concat filter; the rest overlap via xfade/acrossfade.
+pub fn segment_join_graph(segments: &[PreparedSegment], width: u32, height: u32, fps: f64) -> String {
+    let mut parts = Vec::new();
+    
+    // xfade needs identical size, rate and timebase on both sides
+    for i in 0..segments.len() {
+        parts.push(format!(
+            "[{i}:v]scale={width}:{height},fps={fps},format=yuv420p,settb=AVTB,setpts=PTS-STARTPTS[v{i}]"
+        ));
+        parts.push(format!("[{i}:a]aresample=48000,asetpts=PTS-STARTPTS[a{i}]"));
+    }
+    
+    let mut video = "v0".to_string();
+    let mut audio = "a0".to_string();
+    let mut elapsed_ms = segments.first().map_or(0, |s| s.duration_ms);
+    
+    for (i, segment) in segments.iter().enumerate().skip(1) {
+        let video_out = format!("vj{i}");
+        let audio_out = format!("aj{i}");
+        
+        match &segment.transition_in {
+            Some(transition) => {
+                let duration_sec = transition.duration_ms as f64 / 1000.0;
+                let offset_sec = elapsed_ms.saturating_sub(transition.duration_ms) as f64 / 1000.0;
+                let curve = acrossfade_curve(transition.audio_curve);
+                
+                parts.push(format!(
+                    "[{video}][v{i}]xfade=transition={}:duration={:.3}:offset={:.3}[{video_out}]",
+                    xfade_transition(transition.kind), duration_sec, offset_sec
+                ));
+                parts.push(format!(
+                    "[{audio}][a{i}]acrossfade=d={:.3}:c1={curve}:c2={curve}[{audio_out}]",
+                    duration_sec
+                ));
+                elapsed_ms = elapsed_ms + segment.duration_ms - transition.duration_ms;
+            }
+            None => {
+                parts.push(format!(
+                    "[{video}][{audio}][v{i}][a{i}]concat=n=2:v=1:a=1[{video_out}][{audio_out}]"
+                ));
+                elapsed_ms += segment.duration_ms;
+            }
+        }
+        
+        video = video_out;
+        audio = audio_out;
+    }
+    
+    parts.push(format!("[{video}]null[vout]"));
+    parts.push(format!("[{audio}]anull[aout]"));
+    parts.join(";")
+}
+
+// Mix labelled audio streams; the output follows `duration` ("first" or "longest")
+fn amix(graph: &mut FilterGraph, inputs: &[String], duration: &str) -> String {
+    if inputs.len() == 1 {
+        return inputs[0].clone();
+    }
+    
+    let output = graph.label("mix");
+    let pads: String = inputs.iter().map(|i| format!("[{}]", i)).collect();
+    graph.push(format!(
+        "{pads}amix=inputs={}:duration={duration}:normalize=0[{output}]",
+        inputs.len()
+    ));
+    output
+}
+
+/// Mix audio track clips with the graph's main audio, applying gain, mute/solo and sidechain ducking.
+/// The main audio stays first in the final mix so the output keeps the timeline's length.
+pub fn mix_audio_tracks(graph: &mut FilterGraph, tracks: &[ExportAudioTrack]) {
+    let any_solo = tracks.iter().any(|t| t.solo);
+    
+    let main = graph.label("main");
+    let main_audio = graph.audio.clone();
+    graph.push(format!("[{main_audio}]volume={}[{main}]", if any_solo { 0 } else { 1 }));
+    
+    let mut dialogue = vec![main];
+    let mut ducked = Vec::new();
+    
+    for track in tracks.iter().filter(|t| !t.muted && (!any_solo || t.solo)) {
+        let mut clips = Vec::new();
+        
+        for clip in &track.clips {
+            let in_sec = format!("{:.3}", clip.in_ms as f64 / 1000.0);
+            let duration_sec = format!("{:.3}", clip.out_ms.saturating_sub(clip.in_ms) as f64 / 1000.0);
+            let input = graph.add_input(&["-ss", &in_sec, "-t", &duration_sec, "-i", &clip.asset_path]);
+            
+            // Delay each clip to its timeline position
+            let label = graph.label("track");
+            graph.push(format!(
+                "[{input}:a]aresample=48000,aformat=channel_layouts=stereo,volume={:.2}dB,adelay={}:all=1[{label}]",
+                clip.gain_db + track.volume_db, clip.start_ms
+            ));
+            clips.push(label);
+        }
+        
+        if clips.is_empty() {
+            continue;
+        }
+        
+        match &track.ducking {
+            Some(settings) => {
+                let bed = amix(graph, &clips, "longest");
+                ducked.push((bed, settings));
+            }
+            None => dialogue.extend(clips),
+        }
+    }
+    
+    let dialogue_mix = amix(graph, &dialogue, "first");
+    
+    if ducked.is_empty() {
+        graph.audio = dialogue_mix;
+        return;
+    }
+    
+    // One copy of the dialogue goes to the output, one keys each ducked track
+    let keys: Vec<String> = (0..=ducked.len()).map(|_| graph.label("key")).collect();
+    let key_pads: String = keys.iter().map(|k| format!("[{}]", k)).collect();
+    graph.push(format!("[{dialogue_mix}]asplit={}{key_pads}", keys.len()));
+    
+    let mut final_mix = vec![keys[0].clone()];
+    for ((bed, settings), key) in ducked.iter().zip(&keys[1..]) {
+        let output = graph.label("ducked");
+        graph.push(format!(
+            "[{bed}][{key}]sidechaincompress=threshold={}:ratio={}:attack={}:release={}[{output}]",
+            settings.threshold, settings.ratio, settings.attack_ms, settings.release_ms
+        ));
+        final_mix.push(output);
+    }
+    
+    graph.audio = amix(graph, &final_mix, "first");
+}
+
+/// Alpha expression fading in after `start` and out before `end` (all in seconds)
+fn fade_alpha(start: f64, end: f64, fade_in: f64, fade_out: f64) -> String {
+    let fade_out_expr = if fade_out > 0.0 {
+        format!("if(gt(t,{:.3}),({:.3}-t)/{:.3},1)", end - fade_out, end, fade_out)
+    } else {
+        "1".to_string()
+    };
+    
+    if fade_in > 0.0 {
+        format!("if(lt(t,{:.3}),(t-{:.3})/{:.3},{})", start + fade_in, start, fade_in, fade_out_expr)
+    } else {
+        fade_out_expr
+    }
+}
+
+/// Draw text overlays over the graph's video with drawtext
+pub fn draw_text_overlays(graph: &mut FilterGraph, overlays: &[TextOverlay]) {
+    for overlay in overlays {
+        let start = overlay.start_ms as f64 / 1000.0;
+        let end = overlay.end_ms as f64 / 1000.0;
+        
+        // expansion=none keeps '%' in user text literal
+        let mut options = vec![
+            format!("fontfile={}", filter_graph_value(&overlay.font_file)),
+            format!("text={}", filter_graph_value(&overlay.text)),
+            "expansion=none".to_string(),
+            format!("fontsize={}", overlay.font_size),
+            format!("fontcolor={}", overlay.color),
+            format!("x={}", overlay.x),
+            format!("y={}", overlay.y),
+        ];
+        
+        if let Some(box_color) = &overlay.box_color {
+            options.push("box=1".to_string());
+            options.push(format!("boxcolor={}", box_color));
+            options.push(format!("boxborderw={}", overlay.box_padding));
+        }
+        
+        options.push(format!(
+            "alpha='{}'",
+            fade_alpha(start, end, overlay.fade_in_ms as f64 / 1000.0, overlay.fade_out_ms as f64 / 1000.0)
+        ));
+        options.push(format!("enable='between(t,{:.3},{:.3})'", start, end));
+        
+        let output = graph.label("text");
+        let base = graph.video.clone();
+        graph.push(format!("[{base}]drawtext={}[{output}]", options.join(":")));
+        graph.video = output;
+    }
+}
+
+/// Overlay the watermark image in its corner, sized relative to `frame_width`.
+/// `enable` limits it to a window in seconds; None shows it throughout.
+pub fn apply_watermark(graph: &mut FilterGraph, watermark: &Watermark, frame_width: u32, enable: Option<(f64, f64)>) {
+    let input = graph.add_input(&["-i", &watermark.image_path]);
+    let width = ((frame_width as f64 * watermark.scale).round() as u32).max(1);
+    
+    let logo = graph.label("logo");
+    graph.push(format!(
+        "[{input}:v]scale={width}:-1,format=rgba,colorchannelmixer=aa={}[{logo}]",
+        watermark.opacity
+    ));
+    
+    let margin = watermark.margin;
+    let (x, y) = match watermark.corner {
+        Corner::TopLeft => (format!("{margin}"), format!("{margin}")),
+        Corner::TopRight => (format!("W-w-{margin}"), format!("{margin}")),
+        Corner::BottomLeft => (format!("{margin}"), format!("H-h-{margin}")),
+        Corner::BottomRight => (format!("W-w-{margin}"), format!("H-h-{margin}")),
+    };
+    
+    let output = graph.label("watermarked");
+    let base = graph.video.clone();
+    // The logo is a single frame, so hold it for the whole window rather than passing the base
+    // through once it ends the way overlay_filter does for clips
+    match enable {
+        Some((start, end)) => graph.push(format!(
+            "[{base}][{logo}]overlay={x}:{y}:eof_action=repeat:enable='between(t,{:.3},{:.3})'[{output}]",
+            start, end
+        )),
+        None => graph.push(overlay_filter(&base, &logo, &x, &y, None, &output)),
+    }
+    graph.video = output;
+}
+
+/// atempo factors multiplying to `speed`, each within the 0.5-2.0 range every ffmpeg version accepts
+pub fn atempo_chain(speed: f64) -> String {
+    let mut remaining = speed;
+    let mut factors = Vec::new();
+    
+    while remaining > 2.0 {
+        factors.push(2.0);
+        remaining /= 2.0;
+    }
+    while remaining < 0.5 {
+        factors.push(0.5);
+        remaining /= 0.5;
+    }
+    factors.push(remaining);
+    
+    factors
+        .iter()
+        .map(|f| format!("atempo={:.6}", f))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Retime the graph's video and audio by `speed`, optionally playing backwards.
+/// Audio is left alone when it is generated silence rather than the source's own track.
+pub fn apply_speed(graph: &mut FilterGraph, speed: f64, reverse: bool, has_audio: bool) {
+    let mut video_filters = Vec::new();
+    let mut audio_filters = Vec::new();
+    
+    if reverse {
+        video_filters.push("reverse".to_string());
+        audio_filters.push("areverse".to_string());
+    }
+    if speed != 1.0 {
+        video_filters.push(format!("setpts=(PTS-STARTPTS)/{}", speed));
+        audio_filters.push(atempo_chain(speed));
+    }
+    
+    if !video_filters.is_empty() {
+        let output = graph.label("retimed");
+        graph.push(format!("[{}]{}[{}]", graph.video, video_filters.join(","), output));
+        graph.video = output;
+    }
+    if has_audio && !audio_filters.is_empty() {
+        let output = graph.label("retimed");
+        graph.push(format!("[{}]{}[{}]", graph.audio, audio_filters.join(","), output));
+        graph.audio = output;
+    }
+}
+
+/// Length of the automatic audio fade applied at cuts of de-clicked clips
+pub const DECLICK_MS: u64 = 10;
+
+/// Apply a clip's gain and fades to its segment, `duration_ms` long on the timeline.
+/// De-click turns each cut into a DECLICK_MS audio fade unless a longer fade is already set.
+pub fn apply_clip_levels(graph: &mut FilterGraph, clip: &ExportClip, duration_ms: u64, has_audio: bool) {
+    let seconds = |ms: u64| ms as f64 / 1000.0;
+    
+    let mut video_filters = Vec::new();
+    if clip.video_fade_in_ms > 0 {
+        video_filters.push(format!("fade=t=in:st=0:d={:.3}", seconds(clip.video_fade_in_ms)));
+    }
+    if clip.video_fade_out_ms > 0 {
+        video_filters.push(format!(
+            "fade=t=out:st={:.3}:d={:.3}",
+            seconds(duration_ms.saturating_sub(clip.video_fade_out_ms)),
+            seconds(clip.video_fade_out_ms)
+        ));
+    }
+    
+    let declick_ms = if clip.declick { DECLICK_MS } else { 0 };
+    let audio_fade_in_ms = clip.audio_fade_in_ms.max(declick_ms);
+    let audio_fade_out_ms = clip.audio_fade_out_ms.max(declick_ms);
+    
+    let mut audio_filters = Vec::new();
+    if clip.gain_db != 0.0 {
+        audio_filters.push(format!("volume={:.2}dB", clip.gain_db));
+    }
+    if audio_fade_in_ms > 0 {
+        audio_filters.push(format!("afade=t=in:st=0:d={:.3}", seconds(audio_fade_in_ms)));
+    }
+    if audio_fade_out_ms > 0 {
+        audio_filters.push(format!(
+            "afade=t=out:st={:.3}:d={:.3}",
+            seconds(duration_ms.saturating_sub(audio_fade_out_ms)),
+            seconds(audio_fade_out_ms)
+        ));
+    }
+    
+    if !video_filters.is_empty() {
+        let output = graph.label("faded");
+        graph.push(format!("[{}]{}[{}]", graph.video, video_filters.join(","), output));
+        graph.video = output;
+    }
+    if has_audio && !audio_filters.is_empty() {
+        let output = graph.label("leveled");
+        graph.push(format!("[{}]{}[{}]", graph.audio, audio_filters.join(","), output));
+        graph.audio = output;
+    }
+}
+
+/// Burn an SRT file into the graph's video with libass, using `style` as the ASS override
+pub fn burn_subtitles(graph: &mut FilterGraph, srt_path: &str, style: &SubtitleStyle) {
+    // ASS numpad alignment, horizontally centred
+    let alignment = match style.position {
+        SubtitlePosition::Bottom => 2,
+        SubtitlePosition::Middle => 5,
+        SubtitlePosition::Top => 8,
+    };
+    
+    let mut force_style = vec![
+        format!("FontSize={}", style.font_size),
+        format!("Outline={}", style.outline),
+        format!("Alignment={}", alignment),
+    ];
+    if let Some(font_name) = &style.font_name {
+        force_style.insert(0, format!("FontName={}", font_name));
+    }
+    
+    let output = graph.label("subtitled");
+    graph.push(format!(
+        "[{}]subtitles=filename={}:force_style={}[{}]",
+        graph.video,
+        filter_graph_value(srt_path),
+        filter_graph_value(&force_style.join(",")),
+        output
+    ));
+    graph.video = output;
+}
+
+/// Crop, rotate and flip a clip's video, in that order
+pub fn apply_transform(graph: &mut FilterGraph, clip: &ExportClip) {
+    let mut filters = Vec::new();
+    
+    if let Some(crop) = &clip.crop {
+        filters.push(format!("crop={}:{}:{}:{}", crop.width, crop.height, crop.x, crop.y));
+    }
+    match clip.rotation % 360 {
+        90 => filters.push("transpose=clock".to_string()),
+        180 => filters.push("hflip,vflip".to_string()),
+        270 => filters.push("transpose=cclock".to_string()),
+        _ => {}
+    }
+    if clip.flip_horizontal {
+        filters.push("hflip".to_string());
+    }
+    if clip.flip_vertical {
+        filters.push("vflip".to_string());
+    }
+    
+    if !filters.is_empty() {
+        let output = graph.label("transformed");
+        graph.push(format!("[{}]{}[{}]", graph.video, filters.join(","), output));
+        graph.video = output;
+    }
+}
+
+/// Apply a clip's eq adjustments, white balance and 3D LUT, in that order
+pub fn apply_color(graph: &mut FilterGraph, clip: &ExportClip) {
+    let mut filters = Vec::new();
+    
+    if let Some(color) = &clip.color {
+        filters.push(format!(
+            "eq=brightness={}:contrast={}:saturation={}:gamma={}",
+            color.brightness, color.contrast, color.saturation, color.gamma
+        ));
+        if let Some(temperature) = color.temperature {
+            filters.push(format!("colortemperature=temperature={}", temperature));
+        }
+    }
+    if let Some(lut_path) = &clip.lut_path {
+        filters.push(format!("lut3d=file={}", filter_graph_value(lut_path)));
+    }
+    
+    if !filters.is_empty() {
+        let output = graph.label("graded");
+        graph.push(format!("[{}]{}[{}]", graph.video, filters.join(","), output));
+        graph.video = output;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn option_level_escapes_colons_quotes_and_backslashes() {
+        assert_eq!(escape_filter_option("12:30"), r"12\:30");
+        assert_eq!(escape_filter_option("it's"), r"it\'s");
+        assert_eq!(escape_filter_option(r"C:\fonts"), r"C\:\\fonts");
+    }
+    
+    #[test]
+    fn option_level_leaves_graph_characters_alone() {
+        assert_eq!(escape_filter_option("a,b;c[d]"), "a,b;c[d]");
+        assert_eq!(escape_filter_option("100%"), "100%");
+        assert_eq!(escape_filter_option("line 1\nline 2"), "line 1\nline 2");
+    }
+    
+    #[test]
+    fn graph_level_escapes_separators_and_labels() {
+        assert_eq!(escape_filter_graph("a,b;c[d]"), r"a\,b\;c\[d\]");
+        assert_eq!(escape_filter_graph(r"it\'s"), r"it\\\'s");
+        assert_eq!(escape_filter_graph("12:30"), "12:30");
+    }
+    
+    #[test]
+    fn filter_graph_value_escapes_both_levels() {
+        assert_eq!(filter_graph_value("12:30"), r"12\\:30");
+        assert_eq!(filter_graph_value("it's"), r"it\\\'s");
+        assert_eq!(filter_graph_value(r"C:\fonts"), r"C\\:\\\\fonts");
+        assert_eq!(filter_graph_value("a,b;c[d]"), r"a\,b\;c\[d\]");
+    }
+    
+    #[test]
+    fn filter_graph_value_keeps_percent_and_newlines_literal() {
+        // drawtext gets user text with expansion=none, so '%' needs no escaping
+        assert_eq!(filter_graph_value("100% done"), "100% done");
+        assert_eq!(filter_graph_value("line 1\nline 2"), "line 1\nline 2");
+    }
+    
+    #[test]
+    fn filter_graph_value_handles_mixed_text() {
+        assert_eq!(
+            filter_graph_value("Q&A: Tom's [live], 50%;\nend\\"),
+            concat!(r"Q&A\\: Tom\\\'s \[live\]\, 50%\;", "\n", r"end\\\\")
+        );
+    }
+}
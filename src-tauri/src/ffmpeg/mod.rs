@@ -1,6 +1,8 @@
+pub mod filters;
 pub mod parsers;
 pub mod paths;
 
+pub use filters::*;
 pub use parsers::*;
 pub use paths::*;
 
@@ -1,3 +1,6 @@
+use crate::types::{FFprobeStream, LoudnessMeasurement};
+use std::collections::HashMap;
+
 /// Parse frame rate string like "30/1" or "30000/1001"
 pub fn parse_frame_rate(rate_str: &str) -> Option<f64> {
     let parts: Vec<&str> = rate_str.split('/').collect();
@@ -36,3 +39,40 @@ pub fn calculate_thumbnail_time(duration_ms: u64) -> u64 {
     ten_percent.max(500).min(5000)
 }
 
+
+/// Parse the JSON block loudnorm prints at the end of stderr with print_format=json
+pub fn parse_loudnorm_summary(stderr: &str) -> Option<LoudnessMeasurement> {
+    let start = stderr.rfind('{')?;
+    let end = start + stderr[start..].find('}')?;
+    let fields: HashMap<String, String> = serde_json::from_str(&stderr[start..=end]).ok()?;
+    
+    // Values are strings and may be "-inf" for silence
+    let value = |key: &str| fields.get(key)?.trim().parse::<f64>().ok();
+    
+    Some(LoudnessMeasurement {
+        integrated_lufs: value("input_i")?,
+        true_peak_db: value("input_tp")?,
+        lra: value("input_lra")?,
+        threshold_lufs: value("input_thresh")?,
+        target_offset_db: value("target_offset")?,
+    })
+}
+
+/// Clockwise rotation (0, 90, 180 or 270) a video stream needs to display upright.
+/// Newer ffprobe reports a display matrix in side data, older versions a "rotate" tag.
+pub fn parse_stream_rotation(stream: &FFprobeStream) -> u32 {
+    let from_side_data = stream
+        .side_data_list
+        .iter()
+        .flatten()
+        .find_map(|d| d.rotation)
+        .map(|r| -r);
+    let from_tag = stream
+        .tags
+        .as_ref()
+        .and_then(|t| t.get("rotate"))
+        .and_then(|r| r.parse::<f64>().ok());
+    
+    let degrees = from_side_data.or(from_tag).unwrap_or(0.0);
+    ((degrees / 90.0).round() as i64 * 90).rem_euclid(360) as u32
+}
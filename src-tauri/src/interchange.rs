@@ -0,0 +1,973 @@
+// Timeline interchange with other editors: CMX3600 EDL, FCPXML and OpenTimelineIO JSON
+
+use serde_json::json;
+
+/// A main-track clip with the source details the formats need. Frame counts are derived from
+/// `fps`, the source's own rate, so source timecodes land on real frames.
+#[derive(Debug, Clone)]
+pub struct InterchangeClip {
+    pub path: String,
+    pub source_in_ms: u64,
+    pub source_out_ms: u64,
+    pub start_ms: u64,     // Timeline position
+    pub duration_ms: u64,  // Length on the timeline, after speed changes and freezes
+    pub speed: f64,
+    pub reverse: bool,
+    pub freeze: bool,
+    pub fps: f64,
+    pub width: u32,
+    pub height: u32,
+    pub has_audio: bool,
+    pub source_duration_ms: u64,
+}
+
+impl InterchangeClip {
+    /// File name without directories, used as the clip name
+    pub fn name(&self) -> String {
+        std::path::Path::new(&self.path)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| self.path.clone())
+    }
+    
+    /// Signed playback rate: negative when reversed, zero for a freeze frame
+    fn rate(&self) -> f64 {
+        match (self.freeze, self.reverse) {
+            (true, _) => 0.0,
+            (false, true) => -self.speed,
+            (false, false) => self.speed,
+        }
+    }
+}
+
+pub fn ms_to_frames(ms: u64, fps: f64) -> u64 {
+    (ms as f64 * fps / 1000.0).round() as u64
+}
+
+/// Non-drop-frame timecode at the rate's nominal frame count (30 for 29.97)
+pub fn format_timecode(frames: u64, fps: f64) -> String {
+    let nominal = (fps.round() as u64).max(1);
+    format!(
+        "{:02}:{:02}:{:02}:{:02}",
+        frames / (nominal * 3600),
+        (frames / (nominal * 60)) % 60,
+        (frames / nominal) % 60,
+        frames % nominal
+    )
+}
+
+// EDL record timecodes conventionally start at one hour
+const RECORD_START_HOURS: u64 = 1;
+
+/// CMX3600 EDL of the clips. Reels are numbered per source file and the file is named in
+/// FROM CLIP NAME / SOURCE FILE comments; retimes and freezes get M2 motion lines.
+pub fn to_edl(clips: &[InterchangeClip], title: &str, timeline_fps: f64) -> String {
+    let mut edl = format!("TITLE: {}\nFCM: NON-DROP FRAME\n", title);
+    let record_offset = RECORD_START_HOURS * 3600 * (timeline_fps.round() as u64);
+    let mut reels: Vec<&str> = Vec::new();
+    
+    for (i, clip) in clips.iter().enumerate() {
+        let reel_index = match reels.iter().position(|p| *p == clip.path) {
+            Some(index) => index,
+            None => {
+                reels.push(&clip.path);
+                reels.len() - 1
+            }
+        };
+        let reel = format!("AX{:03}", reel_index + 1);
+        let tracks = if clip.has_audio { "AA/V" } else { "V" };
+        
+        let source_in = ms_to_frames(clip.source_in_ms, clip.fps);
+        let source_out = ms_to_frames(clip.source_out_ms, clip.fps).max(source_in + 1);
+        let record_in = record_offset + ms_to_frames(clip.start_ms, timeline_fps);
+        let record_out = record_offset + ms_to_frames(clip.start_ms + clip.duration_ms, timeline_fps);
+        
+        // A freeze plays no source, so its source range is the single held frame
+        let source_out = if clip.freeze { source_in + 1 } else { source_out };
+        
+        edl.push_str(&format!(
+            "\n{:03}  {:<8} {:<5} C        {} {} {} {}\n",
+            i + 1,
+            reel,
+            tracks,
+            format_timecode(source_in, clip.fps),
+            format_timecode(source_out, clip.fps),
+            format_timecode(record_in, timeline_fps),
+            format_timecode(record_out, timeline_fps)
+        ));
+        
+        if clip.rate() != 1.0 {
+            edl.push_str(&format!(
+                "M2   {:<8}       {:05.1}                {}\n",
+                reel,
+                clip.rate() * clip.fps,
+                format_timecode(source_in, clip.fps)
+            ));
+        }
+        
+        edl.push_str(&format!("* FROM CLIP NAME: {}\n", clip.name()));
+        edl.push_str(&format!("* SOURCE FILE: {}\n", clip.path));
+    }
+    
+    edl
+}
+
+fn escape_xml(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// file:// URL for a local path, percent-encoding anything outside the unreserved set
+pub fn file_url(path: &str) -> String {
+    let path = path.replace('\\', "/");
+    let path = if path.starts_with('/') { path } else { format!("/{}", path) };
+    let encoded: String = path
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' | b':' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect();
+    format!("file://{}", encoded)
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
+/// Frame duration as a rational number of seconds. NTSC rates (29.97 etc.) become 1001/30000;
+/// other fractional rates are taken to the nearest 1/1000 fps, so 12.5 becomes 2/25.
+fn frame_duration(fps: f64) -> (u64, u64) {
+    let ntsc = fps * 1.001;
+    if (fps - fps.round()).abs() < 0.01 {
+        (100, fps.round() as u64 * 100)
+    } else if (ntsc - ntsc.round()).abs() < 0.01 {
+        (1001, ntsc.round() as u64 * 1000)
+    } else {
+        let millifps = ((fps * 1000.0).round() as u64).max(1);
+        let divisor = gcd(1000, millifps);
+        (1000 / divisor, millifps / divisor)
+    }
+}
+
+/// FCPXML time value for a frame count at `fps`
+fn fcpxml_time(frames: u64, fps: f64) -> String {
+    let (numerator, denominator) = frame_duration(fps);
+    if frames == 0 {
+        return "0s".to_string();
+    }
+    format!("{}/{}s", frames * numerator, denominator)
+}
+
+/// FCPXML 1.9 project with the clips on the primary storyline. Gaps between clips become
+/// <gap> elements; retimes and reverses are written as a linear timeMap.
+pub fn to_fcpxml(clips: &[InterchangeClip], title: &str, timeline_fps: f64) -> String {
+    let title = escape_xml(title);
+    let mut resources = Vec::new();
+    let mut formats: Vec<(f64, u32, u32)> = Vec::new();
+    let mut assets: Vec<&str> = Vec::new();
+    let mut spine = Vec::new();
+    
+    let format_id = |formats: &mut Vec<(f64, u32, u32)>, resources: &mut Vec<String>, key: (f64, u32, u32)| {
+        let index = match formats.iter().position(|f| *f == key) {
+            Some(index) => index,
+            None => {
+                let (numerator, denominator) = frame_duration(key.0);
+                resources.push(format!(
+                    "        <format id=\"f{}\" frameDuration=\"{}/{}s\" width=\"{}\" height=\"{}\"/>",
+                    formats.len() + 1, numerator, denominator, key.1, key.2
+                ));
+                formats.push(key);
+                formats.len() - 1
+            }
+        };
+        format!("f{}", index + 1)
+    };
+    
+    let (sequence_width, sequence_height) = clips.first().map(|c| (c.width, c.height)).unwrap_or((1920, 1080));
+    let sequence_format = format_id(&mut formats, &mut resources, (timeline_fps, sequence_width, sequence_height));
+    let mut timeline_frames = 0;
+    
+    for clip in clips {
+        let asset_id = match assets.iter().position(|p| *p == clip.path) {
+            Some(index) => format!("a{}", index + 1),
+            None => {
+                let format = format_id(&mut formats, &mut resources, (clip.fps, clip.width, clip.height));
+                assets.push(&clip.path);
+                resources.push(format!(
+                    "        <asset id=\"a{}\" name=\"{}\" src=\"{}\" start=\"0s\" duration=\"{}\" hasVideo=\"1\" hasAudio=\"{}\" format=\"{}\"/>",
+                    assets.len(),
+                    escape_xml(&clip.name()),
+                    escape_xml(&file_url(&clip.path)),
+                    fcpxml_time(ms_to_frames(clip.source_duration_ms, clip.fps), clip.fps),
+                    if clip.has_audio { 1 } else { 0 },
+                    format
+                ));
+                format!("a{}", assets.len())
+            }
+        };
+        
+        let offset = ms_to_frames(clip.start_ms, timeline_fps);
+        let end = ms_to_frames(clip.start_ms + clip.duration_ms, timeline_fps);
+        if offset > timeline_frames {
+            spine.push(format!(
+                "                        <gap name=\"Gap\" offset=\"{}\" duration=\"{}\"/>",
+                fcpxml_time(timeline_frames, timeline_fps),
+                fcpxml_time(offset - timeline_frames, timeline_fps)
+            ));
+        }
+        timeline_frames = end;
+        
+        let start = ms_to_frames(clip.source_in_ms, clip.fps);
+        let duration = fcpxml_time(end - offset, timeline_fps);
+        let mut element = format!(
+            "                        <asset-clip ref=\"{}\" name=\"{}\" offset=\"{}\" start=\"{}\" duration=\"{}\"",
+            asset_id,
+            escape_xml(&clip.name()),
+            fcpxml_time(offset, timeline_fps),
+            fcpxml_time(start, clip.fps),
+            duration
+        );
+        
+        if clip.rate() == 1.0 {
+            element.push_str("/>");
+        } else {
+            // Map the clip's local time (starting at `start`) onto the source range it plays
+            let local_end_ms = clip.source_in_ms + clip.duration_ms;
+            let (from_ms, to_ms) = match (clip.freeze, clip.reverse) {
+                (true, _) => (clip.source_in_ms, clip.source_in_ms),
+                (false, true) => (clip.source_out_ms, clip.source_in_ms),
+                (false, false) => (clip.source_in_ms, clip.source_out_ms),
+            };
+            element.push_str(&format!(
+                ">\n                            <timeMap>\n                                <timept time=\"{}\" value=\"{}\" interp=\"linear\"/>\n                                <timept time=\"{}\" value=\"{}\" interp=\"linear\"/>\n                            </timeMap>\n                        </asset-clip>",
+                fcpxml_time(start, clip.fps),
+                fcpxml_time(ms_to_frames(from_ms, clip.fps), clip.fps),
+                fcpxml_time(ms_to_frames(local_end_ms, clip.fps), clip.fps),
+                fcpxml_time(ms_to_frames(to_ms, clip.fps), clip.fps)
+            ));
+        }
+        spine.push(element);
+    }
+    
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<!DOCTYPE fcpxml>\n<fcpxml version=\"1.9\">\n    <resources>\n{}\n    </resources>\n    <library>\n        <event name=\"{}\">\n            <project name=\"{}\">\n                <sequence format=\"{}\" duration=\"{}\" tcStart=\"0s\" tcFormat=\"NDF\">\n                    <spine>\n{}\n                    </spine>\n                </sequence>\n            </project>\n        </event>\n    </library>\n</fcpxml>\n",
+        resources.join("\n"),
+        title,
+        title,
+        sequence_format,
+        fcpxml_time(timeline_frames, timeline_fps),
+        spine.join("\n")
+    )
+}
+
+fn otio_time(frames: u64, fps: f64) -> serde_json::Value {
+    json!({
+        "OTIO_SCHEMA": "RationalTime.1",
+        "rate": fps,
+        "value": frames as f64,
+    })
+}
+
+fn otio_range(start: u64, duration: u64, fps: f64) -> serde_json::Value {
+    json!({
+        "OTIO_SCHEMA": "TimeRange.1",
+        "start_time": otio_time(start, fps),
+        "duration": otio_time(duration, fps),
+    })
+}
+
+/// OpenTimelineIO timeline with a video track and, for clips with sound, a matching audio track.
+/// Source ranges are in source frames and last as long as the clip does on the timeline;
+/// retimes are LinearTimeWarp effects and freezes FreezeFrame effects, as OTIO's own EDL adapter writes them.
+pub fn to_otio(clips: &[InterchangeClip], title: &str, timeline_fps: f64) -> String {
+    let mut video = Vec::new();
+    let mut audio = Vec::new();
+    let mut video_frames = 0;
+    let mut audio_frames = 0;
+    
+    for clip in clips {
+        let offset = ms_to_frames(clip.start_ms, timeline_fps);
+        let end = ms_to_frames(clip.start_ms + clip.duration_ms, timeline_fps);
+        let record_frames = end - offset;
+        
+        let effects = match (clip.freeze, clip.rate()) {
+            (true, _) => vec![json!({
+                "OTIO_SCHEMA": "FreezeFrame.1",
+                "name": "",
+                "effect_name": "FreezeFrame",
+                "time_scalar": 0.0,
+                "metadata": {},
+            })],
+            (false, rate) if rate != 1.0 => vec![json!({
+                "OTIO_SCHEMA": "LinearTimeWarp.1",
+                "name": "",
+                "effect_name": "LinearTimeWarp",
+                "time_scalar": rate,
+                "metadata": {},
+            })],
+            _ => Vec::new(),
+        };
+        
+        // Clip durations are in source frames, which differ from timeline frames when rates differ
+        let source_frames = (record_frames as f64 * clip.fps / timeline_fps).round() as u64;
+        let clip_json = json!({
+            "OTIO_SCHEMA": "Clip.1",
+            "name": clip.name(),
+            "source_range": otio_range(ms_to_frames(clip.source_in_ms, clip.fps), source_frames, clip.fps),
+            "media_reference": {
+                "OTIO_SCHEMA": "ExternalReference.1",
+                "name": clip.name(),
+                "target_url": file_url(&clip.path),
+                "available_range": otio_range(0, ms_to_frames(clip.source_duration_ms, clip.fps), clip.fps),
+                "metadata": {},
+            },
+            "effects": effects,
+            "markers": [],
+            "metadata": {},
+        });
+        
+        let gap = |frames: u64| json!({
+            "OTIO_SCHEMA": "Gap.1",
+            "name": "",
+            "source_range": otio_range(0, frames, timeline_fps),
+            "effects": [],
+            "markers": [],
+            "metadata": {},
+        });
+        
+        if offset > video_frames {
+            video.push(gap(offset - video_frames));
+        }
+        video.push(clip_json.clone());
+        video_frames = end;
+        
+        if clip.has_audio {
+            if offset > audio_frames {
+                audio.push(gap(offset - audio_frames));
+            }
+            audio.push(clip_json);
+            audio_frames = end;
+        }
+    }
+    
+    let track = |name: &str, kind: &str, children: Vec<serde_json::Value>| json!({
+        "OTIO_SCHEMA": "Track.1",
+        "name": name,
+        "kind": kind,
+        "source_range": null,
+        "children": children,
+        "effects": [],
+        "markers": [],
+        "metadata": {},
+    });
+    
+    let timeline = json!({
+        "OTIO_SCHEMA": "Timeline.1",
+        "name": title,
+        "global_start_time": null,
+        "metadata": {},
+        "tracks": {
+            "OTIO_SCHEMA": "Stack.1",
+            "name": "tracks",
+            "source_range": null,
+            "children": [track("V1", "Video", video), track("A1", "Audio", audio)],
+            "effects": [],
+            "markers": [],
+            "metadata": {},
+        },
+    });
+    
+    serde_json::to_string_pretty(&timeline).unwrap_or_default()
+}
+
+/// Source in point of an imported edit
+#[derive(Debug, Clone, PartialEq)]
+pub enum SourceTime {
+    Frames { frames: f64, rate: f64 },
+    Timecode(String),  // EDL timecodes count the media's frames, so they wait until its rate is known
+}
+
+impl SourceTime {
+    pub fn to_ms(&self, media_fps: f64) -> Option<u64> {
+        let (frames, rate) = match self {
+            SourceTime::Frames { frames, rate } => (*frames, *rate),
+            SourceTime::Timecode(tc) => (parse_timecode(tc, media_fps)? as f64, media_fps),
+        };
+        (rate > 0.0).then(|| (frames * 1000.0 / rate).round().max(0.0) as u64)
+    }
+}
+
+/// One edit on the main video track, before its media has been found on disk
+#[derive(Debug, Clone, PartialEq)]
+pub struct InterchangeEvent {
+    pub reel: String,
+    pub clip_name: Option<String>,
+    pub source_file: Option<String>,  // Path the file records for the media, if any
+    pub source_in: SourceTime,
+    pub record_in_ms: u64,  // Timeline position; the first edit of an EDL starts at 0
+    pub record_duration_ms: u64,
+    pub speed: f64,  // Signed; negative plays backwards, zero holds the first frame
+}
+
+/// Parse "hh:mm:ss:ff" (";" before the frames for drop-frame) into a frame count at the
+/// rate's nominal frame count. Drop-frame numbering is not compensated.
+pub fn parse_timecode(value: &str, fps: f64) -> Option<u64> {
+    let parts = value
+        .split([':', ';', '.'])
+        .map(|p| p.parse().ok())
+        .collect::<Option<Vec<u64>>>()?;
+    let [hours, minutes, seconds, frames] = parts.as_slice() else {
+        return None;
+    };
+    let nominal = (fps.round() as u64).max(1);
+    Some(((hours * 60 + minutes) * 60 + seconds) * nominal + frames)
+}
+
+/// Parse the video events of a CMX3600 EDL whose record timecodes run at `fps`. Audio-only
+/// events are skipped, M2 lines set the event's speed, and FROM CLIP NAME / SOURCE FILE
+/// comments name its media. Record times are rebased so the earliest event starts at zero.
+pub fn parse_edl(content: &str, fps: f64) -> Result<Vec<InterchangeEvent>, String> {
+    let mut events: Vec<InterchangeEvent> = Vec::new();
+    let mut record_starts = Vec::new();
+    // Comments and M2 lines belong to the event line above them, if that one was kept
+    let mut current_kept = false;
+    let frames_to_ms = |frames: u64| (frames as f64 * 1000.0 / fps).round() as u64;
+    
+    for line in content.trim_start_matches('\u{feff}').lines() {
+        let line = line.trim();
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        let current = events.last_mut().filter(|_| current_kept);
+        
+        if let Some(comment) = line.strip_prefix('*') {
+            let Some(event) = current else {
+                continue;
+            };
+            let comment = comment.trim();
+            if let Some(name) = comment.strip_prefix("FROM CLIP NAME:") {
+                event.clip_name = Some(name.trim().to_string());
+            } else if let Some(file) = comment.strip_prefix("SOURCE FILE:") {
+                event.source_file = Some(file.trim().to_string());
+            }
+        } else if tokens.first() == Some(&"M2") {
+            // M2 <reel> <speed in frames per second> <source in>
+            let Some(event) = current else {
+                continue;
+            };
+            let rate: f64 = tokens
+                .get(2)
+                .and_then(|r| r.parse().ok())
+                .ok_or_else(|| format!("Invalid motion effect: {}", line))?;
+            event.speed = rate / fps;
+        } else if tokens.len() >= 8 && tokens[0].chars().all(|c| c.is_ascii_digit()) {
+            // <event> <reel> <tracks> <transition> [duration] <src in> <src out> <rec in> <rec out>
+            let timecodes = &tokens[tokens.len() - 4..];
+            let (record_in, record_out) = match (parse_timecode(timecodes[2], fps), parse_timecode(timecodes[3], fps)) {
+                (Some(record_in), Some(record_out)) if parse_timecode(timecodes[0], fps).is_some() => (record_in, record_out),
+                _ => return Err(format!("Invalid timecode in event: {}", line)),
+            };
+            
+            current_kept = tokens[2].contains('V') && record_out > record_in;
+            if current_kept {
+                record_starts.push(record_in);
+                events.push(InterchangeEvent {
+                    reel: tokens[1].to_string(),
+                    clip_name: None,
+                    source_file: None,
+                    source_in: SourceTime::Timecode(timecodes[0].to_string()),
+                    record_in_ms: 0,
+                    record_duration_ms: frames_to_ms(record_out - record_in),
+                    speed: 1.0,
+                });
+            }
+        }
+    }
+    
+    let first_record = record_starts.iter().copied().min().ok_or_else(|| "The EDL has no video events".to_string())?;
+    for (event, record_in) in events.iter_mut().zip(record_starts) {
+        event.record_in_ms = frames_to_ms(record_in - first_record);
+    }
+    Ok(events)
+}
+
+/// Milliseconds of an OTIO RationalTime
+fn otio_ms(value: &serde_json::Value) -> Option<u64> {
+    let frames = value.get("value")?.as_f64()?;
+    let rate = value.get("rate")?.as_f64().filter(|r| *r > 0.0)?;
+    Some((frames * 1000.0 / rate).round().max(0.0) as u64)
+}
+
+/// Local path for a file:// URL (or a plain path), undoing percent-encoding
+pub fn path_from_url(url: &str) -> String {
+    let Some(path) = url.strip_prefix("file://") else {
+        return url.to_string();
+    };
+    // Skip the host part if there is one ("file://localhost/...")
+    let path = &path[path.find('/').unwrap_or(0)..];
+    
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = path
+            .get(i + 1..i + 3)
+            .filter(|_| bytes[i] == b'%')
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    let decoded = String::from_utf8_lossy(&decoded).to_string();
+    
+    // "/C:/media/a.mov" is a Windows drive path
+    match decoded.as_bytes() {
+        [b'/', _, b':', ..] => decoded[1..].to_string(),
+        _ => decoded,
+    }
+}
+
+/// Parse the clips of the first video track of an OTIO timeline. Gaps advance the timeline;
+/// transitions overlap their neighbours and take no time of their own.
+pub fn parse_otio(content: &str) -> Result<Vec<InterchangeEvent>, String> {
+    let timeline: serde_json::Value = serde_json::from_str(content).map_err(|e| format!("Invalid JSON: {}", e))?;
+    let schema = |v: &serde_json::Value| v["OTIO_SCHEMA"].as_str().unwrap_or_default().to_string();
+    
+    if !schema(&timeline).starts_with("Timeline.") {
+        return Err("Not an OTIO timeline".to_string());
+    }
+    
+    let track = timeline["tracks"]["children"]
+        .as_array()
+        .and_then(|tracks| tracks.iter().find(|t| t["kind"] == "Video"))
+        .ok_or_else(|| "The timeline has no video track".to_string())?;
+    
+    let mut events = Vec::new();
+    let mut position_ms = 0;
+    
+    for item in track["children"].as_array().into_iter().flatten() {
+        let kind = schema(item);
+        if !kind.starts_with("Gap.") && !kind.starts_with("Clip.") {
+            continue;
+        }
+        
+        let range = &item["source_range"];
+        let duration_ms = otio_ms(&range["duration"]).ok_or_else(|| format!("{} has no source range", item["name"]))?;
+        
+        if kind.starts_with("Clip.") {
+            let start = &range["start_time"];
+            let (Some(frames), Some(rate)) = (start["value"].as_f64(), start["rate"].as_f64()) else {
+                return Err(format!("{} has no source start", item["name"]));
+            };
+            
+            // Clip.2 keeps several references and names the active one
+            let reference = match item.get("media_references") {
+                Some(references) => &references[item["active_media_reference_key"].as_str().unwrap_or("DEFAULT_MEDIA")],
+                None => &item["media_reference"],
+            };
+            let name = item["name"].as_str().filter(|n| !n.is_empty()).map(str::to_string);
+            
+            let speed = item["effects"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .find_map(|effect| {
+                    let kind = schema(effect);
+                    if kind.starts_with("FreezeFrame.") {
+                        Some(0.0)
+                    } else if kind.starts_with("LinearTimeWarp.") {
+                        effect["time_scalar"].as_f64()
+                    } else {
+                        None
+                    }
+                })
+                .unwrap_or(1.0);
+            
+            events.push(InterchangeEvent {
+                reel: reference["name"].as_str().or(name.as_deref()).unwrap_or_default().to_string(),
+                clip_name: name,
+                source_file: reference["target_url"].as_str().map(path_from_url),
+                source_in: SourceTime::Frames { frames, rate },
+                record_in_ms: position_ms,
+                record_duration_ms: duration_ms,
+                speed,
+            });
+        }
+        position_ms += duration_ms;
+    }
+    
+    if events.is_empty() {
+        return Err("The video track has no clips".to_string());
+    }
+    Ok(events)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    const EDL: &str = "TITLE: Imported
+FCM: NON-DROP FRAME
+
+001  TAPE1    V     C        00:00:05:00 00:00:07:00 01:00:10:00 01:00:12:00
+* FROM CLIP NAME: interview.mov
+* SOURCE FILE: /media/interview.mov
+
+002  TAPE2    A     C        00:00:00:00 00:00:02:00 01:00:12:00 01:00:14:00
+* FROM CLIP NAME: music.wav
+
+003  TAPE3    AA/V  C        00:01:00:00 00:01:04:00 01:00:13:00 01:00:15:00
+M2   TAPE3          050.0                00:01:00:00
+* FROM CLIP NAME: broll.mov
+";
+    
+    const OTIO: &str = r#"{
+        "OTIO_SCHEMA": "Timeline.1",
+        "name": "Imported",
+        "tracks": {
+            "OTIO_SCHEMA": "Stack.1",
+            "children": [
+                {
+                    "OTIO_SCHEMA": "Track.1",
+                    "kind": "Audio",
+                    "children": []
+                },
+                {
+                    "OTIO_SCHEMA": "Track.1",
+                    "kind": "Video",
+                    "children": [
+                        {
+                            "OTIO_SCHEMA": "Clip.2",
+                            "name": "interview",
+                            "source_range": {
+                                "OTIO_SCHEMA": "TimeRange.1",
+                                "start_time": {"OTIO_SCHEMA": "RationalTime.1", "rate": 24.0, "value": 48.0},
+                                "duration": {"OTIO_SCHEMA": "RationalTime.1", "rate": 24.0, "value": 24.0}
+                            },
+                            "active_media_reference_key": "HIGH",
+                            "media_references": {
+                                "DEFAULT_MEDIA": {
+                                    "OTIO_SCHEMA": "ExternalReference.1",
+                                    "name": "proxy",
+                                    "target_url": "file:///proxies/interview.mp4"
+                                },
+                                "HIGH": {
+                                    "OTIO_SCHEMA": "ExternalReference.1",
+                                    "name": "interview.mov",
+                                    "target_url": "file:///media/My%20Interview.mov"
+                                }
+                            },
+                            "effects": []
+                        },
+                        {
+                            "OTIO_SCHEMA": "Transition.1",
+                            "name": "Dissolve",
+                            "in_offset": {"OTIO_SCHEMA": "RationalTime.1", "rate": 24.0, "value": 6.0},
+                            "out_offset": {"OTIO_SCHEMA": "RationalTime.1", "rate": 24.0, "value": 6.0}
+                        },
+                        {
+                            "OTIO_SCHEMA": "Gap.1",
+                            "source_range": {
+                                "OTIO_SCHEMA": "TimeRange.1",
+                                "start_time": {"OTIO_SCHEMA": "RationalTime.1", "rate": 24.0, "value": 0.0},
+                                "duration": {"OTIO_SCHEMA": "RationalTime.1", "rate": 24.0, "value": 12.0}
+                            }
+                        },
+                        {
+                            "OTIO_SCHEMA": "Clip.1",
+                            "name": "hold",
+                            "source_range": {
+                                "OTIO_SCHEMA": "TimeRange.1",
+                                "start_time": {"OTIO_SCHEMA": "RationalTime.1", "rate": 25.0, "value": 100.0},
+                                "duration": {"OTIO_SCHEMA": "RationalTime.1", "rate": 25.0, "value": 50.0}
+                            },
+                            "media_reference": {
+                                "OTIO_SCHEMA": "ExternalReference.1",
+                                "name": "hold.mov",
+                                "target_url": "file://localhost/C:/media/hold.mov"
+                            },
+                            "effects": [{"OTIO_SCHEMA": "FreezeFrame.1", "time_scalar": 0.0}]
+                        },
+                        {
+                            "OTIO_SCHEMA": "Clip.1",
+                            "name": "",
+                            "source_range": {
+                                "OTIO_SCHEMA": "TimeRange.1",
+                                "start_time": {"OTIO_SCHEMA": "RationalTime.1", "rate": 25.0, "value": 0.0},
+                                "duration": {"OTIO_SCHEMA": "RationalTime.1", "rate": 25.0, "value": 25.0}
+                            },
+                            "media_reference": {
+                                "OTIO_SCHEMA": "ExternalReference.1",
+                                "name": "fast.mov",
+                                "target_url": "/media/fast.mov"
+                            },
+                            "effects": [{"OTIO_SCHEMA": "LinearTimeWarp.1", "time_scalar": -2.0}]
+                        }
+                    ]
+                }
+            ]
+        }
+    }"#;
+    
+    fn clip(path: &str, source_in_ms: u64, source_out_ms: u64, start_ms: u64, duration_ms: u64) -> InterchangeClip {
+        InterchangeClip {
+            path: path.to_string(),
+            source_in_ms,
+            source_out_ms,
+            start_ms,
+            duration_ms,
+            speed: 1.0,
+            reverse: false,
+            freeze: false,
+            fps: 25.0,
+            width: 1920,
+            height: 1080,
+            has_audio: true,
+            source_duration_ms: 60_000,
+        }
+    }
+    
+    #[test]
+    fn ms_to_frames_rounds_to_the_nearest_frame() {
+        assert_eq!(ms_to_frames(1000, 25.0), 25);
+        assert_eq!(ms_to_frames(1001, 29.97), 30);
+        assert_eq!(ms_to_frames(19, 25.0), 0);
+        assert_eq!(ms_to_frames(21, 25.0), 1);
+    }
+    
+    #[test]
+    fn timecodes_count_nominal_frames() {
+        assert_eq!(format_timecode(0, 25.0), "00:00:00:00");
+        assert_eq!(format_timecode(((3600 + 60 + 1) * 25) + 12, 25.0), "01:01:01:12");
+        // 29.97 counts 30 frames per timecode second, without dropping any
+        assert_eq!(format_timecode(1800, 29.97), "00:01:00:00");
+        assert_eq!(format_timecode(29, 29.97), "00:00:00:29");
+    }
+    
+    #[test]
+    fn frame_durations_are_exact() {
+        assert_eq!(frame_duration(25.0), (100, 2500));
+        assert_eq!(frame_duration(29.97), (1001, 30000));
+        assert_eq!(frame_duration(23.976), (1001, 24000));
+        assert_eq!(frame_duration(59.94), (1001, 60000));
+        assert_eq!(frame_duration(12.5), (2, 25));
+    }
+    
+    #[test]
+    fn edl_events_start_at_one_hour_and_share_reels_per_file() {
+        let mut fast = clip("/media/b.mov", 0, 4000, 3000, 2000);
+        fast.speed = 2.0;
+        fast.has_audio = false;
+        let clips = [
+            clip("/media/a.mov", 1000, 3000, 0, 2000),
+            fast,
+            clip("/media/a.mov", 5000, 6000, 5000, 1000),
+        ];
+        
+        assert_eq!(
+            to_edl(&clips, "Cut", 25.0),
+            "TITLE: Cut
+FCM: NON-DROP FRAME
+
+001  AX001    AA/V  C        00:00:01:00 00:00:03:00 01:00:00:00 01:00:02:00
+* FROM CLIP NAME: a.mov
+* SOURCE FILE: /media/a.mov
+
+002  AX002    V     C        00:00:00:00 00:00:04:00 01:00:03:00 01:00:05:00
+M2   AX002          050.0                00:00:00:00
+* FROM CLIP NAME: b.mov
+* SOURCE FILE: /media/b.mov
+
+003  AX001    AA/V  C        00:00:05:00 00:00:06:00 01:00:05:00 01:00:06:00
+* FROM CLIP NAME: a.mov
+* SOURCE FILE: /media/a.mov
+"
+        );
+    }
+    
+    #[test]
+    fn edl_freezes_hold_one_source_frame() {
+        let mut freeze = clip("/media/a.mov", 2000, 2000, 0, 3000);
+        freeze.freeze = true;
+        
+        let edl = to_edl(&[freeze], "Hold", 25.0);
+        assert!(edl.contains("00:00:02:00 00:00:02:01 01:00:00:00 01:00:03:00"));
+        assert!(edl.contains("M2   AX001          000.0                00:00:02:00"));
+    }
+    
+    #[test]
+    fn fcpxml_uses_each_sources_frame_duration() {
+        let mut slow = clip("/media/b.mov", 0, 2000, 2000, 2000);
+        slow.fps = 12.5;
+        let clips = [clip("/media/a.mov", 1000, 2000, 0, 1000), slow];
+        
+        let xml = to_fcpxml(&clips, "Rates & Sizes", 29.97);
+        assert!(xml.contains("<format id=\"f1\" frameDuration=\"1001/30000s\" width=\"1920\" height=\"1080\"/>"));
+        assert!(xml.contains("<format id=\"f2\" frameDuration=\"100/2500s\" width=\"1920\" height=\"1080\"/>"));
+        assert!(xml.contains("<format id=\"f3\" frameDuration=\"2/25s\" width=\"1920\" height=\"1080\"/>"));
+        assert!(xml.contains("<event name=\"Rates &amp; Sizes\">"));
+        assert!(xml.contains("src=\"file:///media/b.mov\""));
+        assert!(xml.contains("duration=\"1500/25s\" hasVideo=\"1\" hasAudio=\"1\" format=\"f3\""));
+    }
+    
+    #[test]
+    fn fcpxml_fills_space_with_gaps_and_places_clips_on_timeline_frames() {
+        let clips = [clip("/media/a.mov", 1000, 2000, 0, 1000), clip("/media/b.mov", 0, 1000, 2000, 1000)];
+        
+        let xml = to_fcpxml(&clips, "Gaps", 25.0);
+        assert!(xml.contains(
+            "<asset-clip ref=\"a1\" name=\"a.mov\" offset=\"0s\" start=\"2500/2500s\" duration=\"2500/2500s\"/>"
+        ));
+        assert!(xml.contains("<gap name=\"Gap\" offset=\"2500/2500s\" duration=\"2500/2500s\"/>"));
+        assert!(xml.contains(
+            "<asset-clip ref=\"a2\" name=\"b.mov\" offset=\"5000/2500s\" start=\"0s\" duration=\"2500/2500s\"/>"
+        ));
+        assert!(xml.contains("<sequence format=\"f1\" duration=\"7500/2500s\""));
+    }
+    
+    #[test]
+    fn fcpxml_retimes_with_a_time_map() {
+        let mut reversed = clip("/media/a.mov", 1000, 3000, 0, 2000);
+        reversed.reverse = true;
+        
+        let xml = to_fcpxml(&[reversed], "Reverse", 25.0);
+        assert!(xml.contains("<timept time=\"2500/2500s\" value=\"7500/2500s\" interp=\"linear\"/>"));
+        assert!(xml.contains("<timept time=\"7500/2500s\" value=\"2500/2500s\" interp=\"linear\"/>"));
+    }
+    
+    #[test]
+    fn timecodes_parse_to_nominal_frames() {
+        assert_eq!(parse_timecode("01:00:00:00", 25.0), Some(90_000));
+        assert_eq!(parse_timecode("00:00:01:12", 25.0), Some(37));
+        // Drop-frame separators are accepted but not compensated
+        assert_eq!(parse_timecode("00:01:00;02", 29.97), Some(1802));
+        assert_eq!(parse_timecode("00:00:01", 25.0), None);
+        assert_eq!(parse_timecode("00:00:xx:00", 25.0), None);
+    }
+    
+    #[test]
+    fn edl_video_events_are_rebased_to_zero() {
+        let events = parse_edl(EDL, 25.0).unwrap();
+        
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0], InterchangeEvent {
+            reel: "TAPE1".to_string(),
+            clip_name: Some("interview.mov".to_string()),
+            source_file: Some("/media/interview.mov".to_string()),
+            source_in: SourceTime::Timecode("00:00:05:00".to_string()),
+            record_in_ms: 0,
+            record_duration_ms: 2000,
+            speed: 1.0,
+        });
+        assert_eq!(events[1].record_in_ms, 3000);
+        assert_eq!(events[1].record_duration_ms, 2000);
+    }
+    
+    #[test]
+    fn edl_motion_and_comments_attach_to_the_kept_event_above() {
+        let events = parse_edl(EDL, 25.0).unwrap();
+        
+        assert_eq!(events[1].speed, 2.0);
+        // The audio-only event's comment doesn't leak onto its neighbours
+        assert_eq!(events[0].clip_name.as_deref(), Some("interview.mov"));
+        assert_eq!(events[1].clip_name.as_deref(), Some("broll.mov"));
+        assert_eq!(events[1].source_file, None);
+        assert_eq!(events[1].source_in.to_ms(25.0), Some(60_000));
+    }
+    
+    #[test]
+    fn edl_round_trips_through_the_writer() {
+        let mut fast = clip("/media/b.mov", 0, 4000, 3000, 2000);
+        fast.speed = 2.0;
+        let clips = [clip("/media/a.mov", 1000, 3000, 0, 2000), fast];
+        
+        let events = parse_edl(&to_edl(&clips, "Round trip", 25.0), 25.0).unwrap();
+        let summary: Vec<_> = events
+            .iter()
+            .map(|e| (e.source_file.as_deref(), e.source_in.to_ms(25.0), e.record_in_ms, e.record_duration_ms, e.speed))
+            .collect();
+        assert_eq!(summary, [
+            (Some("/media/a.mov"), Some(1000), 0, 2000, 1.0),
+            (Some("/media/b.mov"), Some(0), 3000, 2000, 2.0),
+        ]);
+    }
+    
+    #[test]
+    fn rejects_malformed_edls() {
+        assert!(parse_edl("TITLE: Empty\n", 25.0).is_err());
+        assert!(parse_edl("001  AX  V  C  00:00:00:00 00:00:01:00 01:00:00:00 01:00:xx:00\n", 25.0).is_err());
+        assert!(parse_edl(
+            "001  AX  V  C  00:00:00:00 00:00:01:00 01:00:00:00 01:00:01:00\nM2   AX   fast   00:00:00:00\n",
+            25.0
+        )
+        .is_err());
+    }
+    
+    #[test]
+    fn otio_reads_the_first_video_track() {
+        let events = parse_otio(OTIO).unwrap();
+        
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[0], InterchangeEvent {
+            reel: "interview.mov".to_string(),
+            clip_name: Some("interview".to_string()),
+            source_file: Some("/media/My Interview.mov".to_string()),
+            source_in: SourceTime::Frames { frames: 48.0, rate: 24.0 },
+            record_in_ms: 0,
+            record_duration_ms: 1000,
+            speed: 1.0,
+        });
+        assert_eq!(events[0].source_in.to_ms(30.0), Some(2000));
+    }
+    
+    #[test]
+    fn otio_gaps_advance_the_timeline_and_effects_set_the_speed() {
+        let events = parse_otio(OTIO).unwrap();
+        
+        // The transition takes no time; the 12-frame gap at 24 fps is 500ms
+        assert_eq!(events[1].record_in_ms, 1500);
+        assert_eq!(events[1].record_duration_ms, 2000);
+        assert_eq!(events[1].speed, 0.0);
+        assert_eq!(events[1].source_file.as_deref(), Some("C:/media/hold.mov"));
+        
+        assert_eq!(events[2].record_in_ms, 3500);
+        assert_eq!(events[2].speed, -2.0);
+        assert_eq!(events[2].clip_name, None);
+        assert_eq!(events[2].reel, "fast.mov");
+        assert_eq!(events[2].source_file.as_deref(), Some("/media/fast.mov"));
+    }
+    
+    #[test]
+    fn rejects_malformed_otio() {
+        assert!(parse_otio("not json").is_err());
+        assert!(parse_otio(r#"{"OTIO_SCHEMA": "Clip.1"}"#).is_err());
+        assert!(parse_otio(r#"{"OTIO_SCHEMA": "Timeline.1", "tracks": {"children": []}}"#).is_err());
+        assert!(parse_otio(
+            r#"{"OTIO_SCHEMA": "Timeline.1", "tracks": {"children": [{"kind": "Video", "children": []}]}}"#
+        )
+        .is_err());
+    }
+    
+    #[test]
+    fn file_urls_round_trip_to_paths() {
+        assert_eq!(path_from_url("file:///media/My%20Interview.mov"), "/media/My Interview.mov");
+        assert_eq!(path_from_url("file://localhost/media/a.mov"), "/media/a.mov");
+        assert_eq!(path_from_url("file:///C:/media/a.mov"), "C:/media/a.mov");
+        assert_eq!(path_from_url("/media/plain.mov"), "/media/plain.mov");
+        // A stray '%' is kept rather than dropped
+        assert_eq!(path_from_url("file:///media/100%.mov"), "/media/100%.mov");
+        
+        let path = "/media/Ünïcode & [brackets].mov";
+        assert_eq!(path_from_url(&file_url(path)), path);
+    }
+}
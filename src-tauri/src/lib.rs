@@ -1,5 +1,11 @@
+mod batch;
 mod commands;
+mod export_log;
 mod ffmpeg;
+mod interchange;
+mod metadata;
+mod playlists;
+mod subtitles;
 mod types;
 
 use std::collections::HashMap;
@@ -13,11 +19,13 @@ use types::*;
 pub fn run() {
     // Initialize recording processes state
     let recording_processes: RecordingProcesses = Arc::new(Mutex::new(HashMap::new()));
+    let export_jobs: ExportJobs = Arc::new(Mutex::new(HashMap::new()));
     
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
         .manage(recording_processes)
+        .manage(export_jobs)
         .invoke_handler(tauri::generate_handler![
             greet,
             ping,
@@ -27,6 +35,12 @@ pub fn run() {
             probe_media_stub,
             export_prepare,
             export_concat,
+            export_cancel,
+            preview_clip_frame,
+            export_log_path,
+            export_clips_separately,
+            export_interchange,
+            import_interchange,
             check_ffmpeg,
             save_recording,
             list_screen_devices,
@@ -0,0 +1,102 @@
+// ffmetadata files carrying container tags and chapters into the final mux
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chapter {
+    pub start_ms: u64,
+    pub end_ms: u64,
+    pub title: String,
+}
+
+/// Backslash-escape the characters ffmetadata treats specially: `=`, `;`, `#`, `\` and newlines
+fn escape_value(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    
+    for c in value.chars() {
+        if matches!(c, '=' | ';' | '#' | '\\' | '\n') {
+            escaped.push('\\');
+        }
+        if c != '\r' {
+            escaped.push(c);
+        }
+    }
+    
+    escaped
+}
+
+/// Turn chapter start points (in output time) into chapters that each run until the next one starts.
+/// Starts at or past the end are dropped; when several share a start, the last one listed wins.
+pub fn chapters_from_starts(mut starts: Vec<(u64, String)>, total_duration_ms: u64) -> Vec<Chapter> {
+    starts.retain(|(start_ms, _)| *start_ms < total_duration_ms);
+    starts.sort_by_key(|(start_ms, _)| *start_ms);
+    
+    let mut chapters: Vec<Chapter> = Vec::new();
+    for (start_ms, title) in starts {
+        match chapters.last_mut() {
+            Some(last) if last.start_ms == start_ms => last.title = title,
+            _ => chapters.push(Chapter {
+                start_ms,
+                end_ms: total_duration_ms,
+                title,
+            }),
+        }
+    }
+    
+    for i in 1..chapters.len() {
+        chapters[i - 1].end_ms = chapters[i].start_ms;
+    }
+    chapters
+}
+
+/// Write global tags and chapters as an `;FFMETADATA1` file with millisecond chapter times
+pub fn to_ffmetadata(tags: &[(&str, &str)], chapters: &[Chapter]) -> String {
+    let mut content = String::from(";FFMETADATA1\n");
+    
+    for (key, value) in tags {
+        content.push_str(&format!("{}={}\n", key, escape_value(value)));
+    }
+    for chapter in chapters {
+        content.push_str(&format!(
+            "\n[CHAPTER]\nTIMEBASE=1/1000\nSTART={}\nEND={}\ntitle={}\n",
+            chapter.start_ms,
+            chapter.end_ms,
+            escape_value(&chapter.title)
+        ));
+    }
+    
+    content
+}
+
+/// Check for an ISO 8601 date-time like "2024-05-01T12:30:00Z", as the mp4 muxer expects for creation_time.
+/// Fractional seconds and a "Z" or "+hh:mm" suffix are optional.
+pub fn is_iso_datetime(value: &str) -> bool {
+    let bytes = value.as_bytes();
+    if bytes.len() < 19 {
+        return false;
+    }
+    
+    let layout_ok = bytes[..19].iter().enumerate().all(|(i, b)| match i {
+        4 | 7 => *b == b'-',
+        10 => *b == b'T' || *b == b' ',
+        13 | 16 => *b == b':',
+        _ => b.is_ascii_digit(),
+    });
+    if !layout_ok {
+        return false;
+    }
+    
+    let mut rest = &value[19..];
+    if let Some(fraction) = rest.strip_prefix('.') {
+        let digits = fraction.chars().take_while(|c| c.is_ascii_digit()).count();
+        if digits == 0 {
+            return false;
+        }
+        rest = &fraction[digits..];
+    }
+    match rest.as_bytes() {
+        [] | [b'Z'] => true,
+        [sign, h1, h2, b':', m1, m2] => {
+            (*sign == b'+' || *sign == b'-') && [h1, h2, m1, m2].iter().all(|b| b.is_ascii_digit())
+        }
+        _ => false,
+    }
+}
@@ -0,0 +1,357 @@
+// Reading the HLS playlists and DASH manifests written by streaming exports
+
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HlsVariant {
+    pub uri: String,  // Relative to the master playlist
+    pub bandwidth: u64,
+    pub resolution: Option<(u32, u32)>,
+    pub codecs: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HlsMediaPlaylist {
+    pub target_duration_s: u64,
+    pub segments: Vec<(String, u64)>,  // Segment URI and duration in ms
+    pub ended: bool,  // Has #EXT-X-ENDLIST, i.e. the playlist is complete
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DashRepresentation {
+    pub id: String,
+    pub mime_type: Option<String>,  // Set on the representation or inherited from its adaptation set
+    pub bandwidth: u64,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DashManifest {
+    pub duration_ms: Option<u64>,
+    pub representations: Vec<DashRepresentation>,
+}
+
+/// Split an HLS attribute list (`BANDWIDTH=1000,CODECS="a,b"`) into keys and unquoted values
+fn parse_attribute_list(list: &str) -> HashMap<String, String> {
+    let mut attributes = HashMap::new();
+    let mut rest = list.trim();
+    
+    while let Some((key, after)) = rest.split_once('=') {
+        let (value, remaining) = match after.strip_prefix('"') {
+            Some(quoted) => {
+                let end = quoted.find('"').unwrap_or(quoted.len());
+                (&quoted[..end], quoted.get(end + 1..).unwrap_or(""))
+            }
+            None => after.split_once(',').unwrap_or((after, "")),
+        };
+        attributes.insert(key.trim().to_string(), value.to_string());
+        rest = remaining.trim_start_matches(',').trim_start();
+    }
+    
+    attributes
+}
+
+fn playlist_lines(content: &str) -> Result<Vec<&str>, String> {
+    let lines: Vec<&str> = content
+        .trim_start_matches('\u{feff}')
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .collect();
+    
+    if lines.first() != Some(&"#EXTM3U") {
+        return Err("Missing #EXTM3U header".to_string());
+    }
+    Ok(lines)
+}
+
+/// Parse a master playlist into its variant streams, in playlist order
+pub fn parse_hls_master(content: &str) -> Result<Vec<HlsVariant>, String> {
+    let lines = playlist_lines(content)?;
+    let mut variants = Vec::new();
+    
+    for (i, line) in lines.iter().enumerate() {
+        let Some(list) = line.strip_prefix("#EXT-X-STREAM-INF:") else {
+            continue;
+        };
+        
+        let attributes = parse_attribute_list(list);
+        let uri = lines
+            .get(i + 1)
+            .filter(|next| !next.starts_with('#'))
+            .ok_or_else(|| format!("Variant without a playlist URI: {}", line))?;
+        let bandwidth = attributes
+            .get("BANDWIDTH")
+            .and_then(|b| b.parse().ok())
+            .ok_or_else(|| format!("Variant without a valid BANDWIDTH: {}", line))?;
+        let resolution = attributes.get("RESOLUTION").and_then(|r| {
+            let (width, height) = r.split_once('x')?;
+            Some((width.parse().ok()?, height.parse().ok()?))
+        });
+        
+        variants.push(HlsVariant {
+            uri: uri.to_string(),
+            bandwidth,
+            resolution,
+            codecs: attributes.get("CODECS").cloned(),
+        });
+    }
+    
+    if variants.is_empty() {
+        return Err("Master playlist lists no variant streams".to_string());
+    }
+    Ok(variants)
+}
+
+/// Parse a media (variant) playlist into its segments
+pub fn parse_hls_media(content: &str) -> Result<HlsMediaPlaylist, String> {
+    let lines = playlist_lines(content)?;
+    let mut target_duration_s = 0;
+    let mut segments = Vec::new();
+    let mut pending_ms = None;
+    let mut ended = false;
+    
+    for line in lines {
+        if let Some(value) = line.strip_prefix("#EXT-X-TARGETDURATION:") {
+            target_duration_s = value
+                .parse()
+                .map_err(|_| format!("Invalid target duration: {}", line))?;
+        } else if let Some(value) = line.strip_prefix("#EXTINF:") {
+            let seconds: f64 = value
+                .split(',')
+                .next()
+                .and_then(|s| s.parse().ok())
+                .ok_or_else(|| format!("Invalid segment duration: {}", line))?;
+            pending_ms = Some((seconds * 1000.0).round() as u64);
+        } else if line == "#EXT-X-ENDLIST" {
+            ended = true;
+        } else if !line.starts_with('#') {
+            let duration_ms = pending_ms
+                .take()
+                .ok_or_else(|| format!("Segment without #EXTINF: {}", line))?;
+            segments.push((line.to_string(), duration_ms));
+        }
+    }
+    
+    Ok(HlsMediaPlaylist {
+        target_duration_s,
+        segments,
+        ended,
+    })
+}
+
+/// Parse an ISO 8601 duration as used by MPD attributes ("PT1M23.456S", "P0Y0M0DT0H0M5.0S")
+fn parse_iso_duration(value: &str) -> Option<u64> {
+    let time = value.strip_prefix('P')?.split_once('T').map(|(_, t)| t).unwrap_or("");
+    let mut seconds = 0.0;
+    let mut number = String::new();
+    
+    for c in time.chars() {
+        match c {
+            'H' | 'M' | 'S' => {
+                let n: f64 = number.parse().ok()?;
+                seconds += n * match c {
+                    'H' => 3600.0,
+                    'M' => 60.0,
+                    _ => 1.0,
+                };
+                number.clear();
+            }
+            _ => number.push(c),
+        }
+    }
+    
+    Some((seconds * 1000.0).round() as u64)
+}
+
+/// Value of `name="..."` inside a single XML start tag; ffmpeg separates attributes with spaces or newlines and tabs
+fn xml_attribute(tag: &str, name: &str) -> Option<String> {
+    let needle = format!("{}=\"", name);
+    let (position, _) = tag
+        .match_indices(&needle)
+        .find(|(i, _)| tag[..*i].ends_with(char::is_whitespace))?;
+    let start = position + needle.len();
+    let end = tag[start..].find('"')?;
+    Some(tag[start..start + end].to_string())
+}
+
+/// Read the duration and representations from an MPD written by ffmpeg's dash muxer.
+/// This is a tag scanner, not a general XML parser; it only needs to understand ffmpeg's layout.
+pub fn parse_dash_manifest(content: &str) -> Result<DashManifest, String> {
+    let mpd_tag = content
+        .split('<')
+        .find(|t| t.starts_with("MPD"))
+        .ok_or_else(|| "Missing <MPD> element".to_string())?;
+    let duration_ms = xml_attribute(mpd_tag, "mediaPresentationDuration").and_then(|d| parse_iso_duration(&d));
+    
+    let mut representations = Vec::new();
+    let mut set_mime_type = None;
+    
+    for tag in content.split('<') {
+        let tag = tag.split('>').next().unwrap_or_default();
+        
+        if tag.starts_with("AdaptationSet") {
+            set_mime_type = xml_attribute(tag, "mimeType").or_else(|| {
+                xml_attribute(tag, "contentType").map(|kind| format!("{}/mp4", kind))
+            });
+        } else if tag.starts_with("Representation") {
+            representations.push(DashRepresentation {
+                id: xml_attribute(tag, "id").unwrap_or_default(),
+                mime_type: xml_attribute(tag, "mimeType").or_else(|| set_mime_type.clone()),
+                bandwidth: xml_attribute(tag, "bandwidth").and_then(|b| b.parse().ok()).unwrap_or(0),
+                width: xml_attribute(tag, "width").and_then(|w| w.parse().ok()),
+                height: xml_attribute(tag, "height").and_then(|h| h.parse().ok()),
+            });
+        }
+    }
+    
+    if representations.is_empty() {
+        return Err("Manifest lists no representations".to_string());
+    }
+    Ok(DashManifest {
+        duration_ms,
+        representations,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    const MASTER: &str = "#EXTM3U
+#EXT-X-VERSION:3
+#EXT-X-STREAM-INF:BANDWIDTH=5540800,RESOLUTION=1920x1080,CODECS=\"avc1.640028,mp4a.40.2\"
+1080p/playlist.m3u8
+
+#EXT-X-STREAM-INF:BANDWIDTH=3221600,RESOLUTION=1280x720,CODECS=\"avc1.64001f,mp4a.40.2\"
+720p/playlist.m3u8
+
+#EXT-X-STREAM-INF:BANDWIDTH=1645600,RESOLUTION=854x480,CODECS=\"avc1.64001e,mp4a.40.2\"
+480p/playlist.m3u8
+";
+    
+    const MEDIA: &str = "#EXTM3U
+#EXT-X-VERSION:6
+#EXT-X-TARGETDURATION:6
+#EXT-X-MEDIA-SEQUENCE:0
+#EXT-X-PLAYLIST-TYPE:VOD
+#EXT-X-INDEPENDENT-SEGMENTS
+#EXTINF:6.000000,
+segment_00000.ts
+#EXTINF:6.000000,
+segment_00001.ts
+#EXTINF:2.480000,
+segment_00002.ts
+#EXT-X-ENDLIST
+";
+    
+    const MANIFEST: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<MPD xmlns="urn:mpeg:dash:schema:mpd:2011"
+	profiles="urn:mpeg:dash:profile:isoff-live:2011"
+	type="static"
+	mediaPresentationDuration="PT14.5S"
+	minBufferTime="PT12.0S">
+	<Period id="0" start="PT0.0S">
+		<AdaptationSet id="0" contentType="video" startWithSAP="1" segmentAlignment="true">
+			<Representation id="0" mimeType="video/mp4" codecs="avc1.640028" bandwidth="5000000" width="1920" height="1080">
+			</Representation>
+			<Representation id="1" mimeType="video/mp4" codecs="avc1.64001f" bandwidth="2800000" width="1280" height="720">
+			</Representation>
+		</AdaptationSet>
+		<AdaptationSet id="1" contentType="audio" startWithSAP="1" segmentAlignment="true">
+			<Representation id="2" codecs="mp4a.40.2" bandwidth="192000" audioSamplingRate="48000">
+			</Representation>
+		</AdaptationSet>
+	</Period>
+</MPD>
+"#;
+    
+    #[test]
+    fn master_playlist_lists_the_ladder_in_order() {
+        let variants = parse_hls_master(MASTER).unwrap();
+        
+        let uris: Vec<&str> = variants.iter().map(|v| v.uri.as_str()).collect();
+        assert_eq!(uris, ["1080p/playlist.m3u8", "720p/playlist.m3u8", "480p/playlist.m3u8"]);
+        let resolutions: Vec<_> = variants.iter().map(|v| v.resolution).collect();
+        assert_eq!(resolutions, [Some((1920, 1080)), Some((1280, 720)), Some((854, 480))]);
+        assert_eq!(variants[0].bandwidth, 5540800);
+        // The quoted CODECS value contains a comma and must stay whole
+        assert_eq!(variants[0].codecs.as_deref(), Some("avc1.640028,mp4a.40.2"));
+    }
+    
+    #[test]
+    fn media_playlist_reads_segment_durations() {
+        let playlist = parse_hls_media(MEDIA).unwrap();
+        
+        assert_eq!(playlist.target_duration_s, 6);
+        assert_eq!(
+            playlist.segments,
+            [
+                ("segment_00000.ts".to_string(), 6000),
+                ("segment_00001.ts".to_string(), 6000),
+                ("segment_00002.ts".to_string(), 2480),
+            ]
+        );
+        assert!(playlist.ended);
+    }
+    
+    #[test]
+    fn media_playlist_without_endlist_is_not_ended() {
+        let playlist = parse_hls_media(&MEDIA.replace("#EXT-X-ENDLIST\n", "")).unwrap();
+        assert!(!playlist.ended);
+    }
+    
+    #[test]
+    fn dash_manifest_reads_duration_and_representations() {
+        let manifest = parse_dash_manifest(MANIFEST).unwrap();
+        
+        assert_eq!(manifest.duration_ms, Some(14500));
+        assert_eq!(manifest.representations.len(), 3);
+        
+        let video = &manifest.representations[1];
+        assert_eq!(video.id, "1");
+        assert_eq!(video.mime_type.as_deref(), Some("video/mp4"));
+        assert_eq!(video.bandwidth, 2800000);
+        assert_eq!((video.width, video.height), (Some(1280), Some(720)));
+        
+        // No mimeType of its own; it comes from the adaptation set's contentType
+        let audio = &manifest.representations[2];
+        assert_eq!(audio.mime_type.as_deref(), Some("audio/mp4"));
+        assert_eq!((audio.width, audio.height), (None, None));
+    }
+    
+    #[test]
+    fn iso_durations_with_all_fields() {
+        assert_eq!(parse_iso_duration("PT1M23.456S"), Some(83456));
+        assert_eq!(parse_iso_duration("P0Y0M0DT1H0M5.0S"), Some(3605000));
+        assert_eq!(parse_iso_duration("PT"), Some(0));
+        assert_eq!(parse_iso_duration("1M"), None);
+    }
+    
+    #[test]
+    fn rejects_playlists_without_a_header() {
+        assert!(parse_hls_master(&MASTER.replacen("#EXTM3U\n", "", 1)).is_err());
+        assert!(parse_hls_media(&MEDIA.replacen("#EXTM3U\n", "", 1)).is_err());
+    }
+    
+    #[test]
+    fn rejects_malformed_master_playlists() {
+        assert!(parse_hls_master("#EXTM3U\n#EXT-X-VERSION:3\n").is_err());
+        assert!(parse_hls_master("#EXTM3U\n#EXT-X-STREAM-INF:BANDWIDTH=1000\n").is_err());
+        assert!(parse_hls_master("#EXTM3U\n#EXT-X-STREAM-INF:RESOLUTION=1280x720\nlow.m3u8\n").is_err());
+    }
+    
+    #[test]
+    fn rejects_malformed_media_playlists() {
+        assert!(parse_hls_media("#EXTM3U\n#EXT-X-TARGETDURATION:six\n").is_err());
+        assert!(parse_hls_media("#EXTM3U\n#EXTINF:abc,\nsegment.ts\n").is_err());
+        assert!(parse_hls_media("#EXTM3U\nsegment.ts\n").is_err());
+    }
+    
+    #[test]
+    fn rejects_malformed_manifests() {
+        assert!(parse_dash_manifest("<Period></Period>").is_err());
+        assert!(parse_dash_manifest("<MPD mediaPresentationDuration=\"PT5S\"><Period></Period></MPD>").is_err());
+    }
+}
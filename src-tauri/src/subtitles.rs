@@ -0,0 +1,197 @@
+// SRT / WebVTT reading and SRT writing for subtitle export
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SubtitleCue {
+    pub start_ms: u64,
+    pub end_ms: u64,
+    pub text: String,
+}
+
+/// Parse a timestamp like "01:02:03,456" (SRT) or "02:03.456" (WebVTT, hours optional)
+fn parse_timestamp(value: &str) -> Option<u64> {
+    let value = value.trim().replace(',', ".");
+    let parts: Vec<&str> = value.split(':').collect();
+    
+    let (hours, minutes, seconds) = match parts.as_slice() {
+        [h, m, s] => (h.parse::<u64>().ok()?, m.parse::<u64>().ok()?, *s),
+        [m, s] => (0, m.parse::<u64>().ok()?, *s),
+        _ => return None,
+    };
+    
+    let (whole, fraction) = seconds.split_once('.').unwrap_or((seconds, "0"));
+    let whole: u64 = whole.parse().ok()?;
+    // Only millisecond precision is kept
+    let millis: u64 = format!("{:0<3}", fraction).get(..3)?.parse().ok()?;
+    
+    Some(((hours * 60 + minutes) * 60 + whole) * 1000 + millis)
+}
+
+fn format_srt_timestamp(ms: u64) -> String {
+    format!(
+        "{:02}:{:02}:{:02},{:03}",
+        ms / 3_600_000,
+        (ms / 60_000) % 60,
+        (ms / 1000) % 60,
+        ms % 1000
+    )
+}
+
+/// Parse SRT or WebVTT (detected by the "WEBVTT" header) into cues.
+/// Blocks without a timing line (WebVTT NOTE/STYLE/REGION, stray text) are skipped.
+pub fn parse_subtitles(content: &str) -> Result<Vec<SubtitleCue>, String> {
+    let content = content.trim_start_matches('\u{feff}').replace("\r\n", "\n");
+    let mut cues = Vec::new();
+    
+    // Cues are separated by blank lines, which some editors leave with spaces or tabs on them
+    let mut blocks: Vec<Vec<&str>> = vec![Vec::new()];
+    for line in content.lines() {
+        match blocks.last_mut() {
+            Some(block) if !line.trim().is_empty() => block.push(line),
+            _ => blocks.push(Vec::new()),
+        }
+    }
+    
+    for block in blocks {
+        let mut lines = block.into_iter().skip_while(|l| !l.contains("-->"));
+        let timing = match lines.next() {
+            Some(timing) => timing,
+            None => continue,
+        };
+        
+        let (start, rest) = timing.split_once("-->").unwrap_or_default();
+        // WebVTT cue settings ("align:start") follow the end timestamp
+        let end = rest.split_whitespace().next().unwrap_or_default();
+        
+        let (start_ms, end_ms) = match (parse_timestamp(start), parse_timestamp(end)) {
+            (Some(start_ms), Some(end_ms)) => (start_ms, end_ms),
+            _ => return Err(format!("Invalid subtitle timing: {}", timing.trim())),
+        };
+        
+        let text = lines.collect::<Vec<_>>().join("\n");
+        if end_ms > start_ms && !text.trim().is_empty() {
+            cues.push(SubtitleCue { start_ms, end_ms, text });
+        }
+    }
+    
+    Ok(cues)
+}
+
+/// Move cues onto a new time base with `map`, clip them to 0..limit_ms and drop any that vanish
+pub fn retime_cues(cues: &[SubtitleCue], map: impl Fn(u64) -> u64, limit_ms: u64) -> Vec<SubtitleCue> {
+    cues.iter()
+        .filter_map(|cue| {
+            let start_ms = map(cue.start_ms).min(limit_ms);
+            let end_ms = map(cue.end_ms).min(limit_ms);
+            (end_ms > start_ms).then(|| SubtitleCue {
+                start_ms,
+                end_ms,
+                text: cue.text.clone(),
+            })
+        })
+        .collect()
+}
+
+pub fn to_srt(cues: &[SubtitleCue]) -> String {
+    cues.iter()
+        .enumerate()
+        .map(|(i, cue)| {
+            format!(
+                "{}\n{} --> {}\n{}\n",
+                i + 1,
+                format_srt_timestamp(cue.start_ms),
+                format_srt_timestamp(cue.end_ms),
+                cue.text
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn cue(start_ms: u64, end_ms: u64, text: &str) -> SubtitleCue {
+        SubtitleCue {
+            start_ms,
+            end_ms,
+            text: text.to_string(),
+        }
+    }
+    
+    #[test]
+    fn timestamps_in_srt_and_webvtt_forms() {
+        assert_eq!(parse_timestamp("01:02:03,456"), Some(3_723_456));
+        assert_eq!(parse_timestamp("01:02:03.456"), Some(3_723_456));
+        assert_eq!(parse_timestamp("02:03.456"), Some(123_456));
+        assert_eq!(parse_timestamp(" 00:00:01,5 "), Some(1500));
+        // Digits past milliseconds are dropped
+        assert_eq!(parse_timestamp("00:00:01.23456"), Some(1234));
+        assert_eq!(parse_timestamp("00:00:07"), Some(7000));
+    }
+    
+    #[test]
+    fn rejects_malformed_timestamps() {
+        assert_eq!(parse_timestamp("1:2:3:4"), None);
+        assert_eq!(parse_timestamp("aa:00:01,000"), None);
+        assert_eq!(parse_timestamp("00:00:01,abc"), None);
+        assert_eq!(parse_timestamp(""), None);
+    }
+    
+    #[test]
+    fn srt_cues_keep_multiline_text() {
+        let srt = "1\r\n00:00:01,000 --> 00:00:02,500\r\nHello\r\nthere\r\n\r\n2\r\n00:00:03,000 --> 00:00:04,000\r\nBye\r\n";
+        
+        assert_eq!(parse_subtitles(srt).unwrap(), [cue(1000, 2500, "Hello\nthere"), cue(3000, 4000, "Bye")]);
+    }
+    
+    #[test]
+    fn whitespace_only_lines_separate_cues() {
+        let srt = "1\n00:00:01,000 --> 00:00:02,000\nFirst\n  \n2\n00:00:03,000 --> 00:00:04,000\nSecond\n\t\n";
+        
+        assert_eq!(parse_subtitles(srt).unwrap(), [cue(1000, 2000, "First"), cue(3000, 4000, "Second")]);
+    }
+    
+    #[test]
+    fn webvtt_skips_header_notes_and_cue_settings() {
+        let vtt = "\u{feff}WEBVTT\n\nNOTE written by hand\n\nSTYLE\n::cue { color: yellow }\n\nintro\n00:01.000 --> 00:02.000 align:start line:0\nHi\n\n00:03.000 --> 00:03.000\nEmpty span\n";
+        
+        assert_eq!(parse_subtitles(vtt).unwrap(), [cue(1000, 2000, "Hi")]);
+    }
+    
+    #[test]
+    fn rejects_invalid_timing_lines() {
+        assert!(parse_subtitles("1\n00:00:01,000 --> soon\nText\n").is_err());
+    }
+    
+    #[test]
+    fn retimed_cues_are_shifted_and_clipped() {
+        let cues = [
+            cue(1000, 2000, "before"),
+            cue(4000, 6000, "across"),
+            cue(9000, 12000, "end"),
+            cue(15000, 16000, "after"),
+        ];
+        
+        // A range starting at 3s and lasting 8s
+        let retimed = retime_cues(&cues, |ms| ms.saturating_sub(3000), 8000);
+        assert_eq!(retimed, [cue(1000, 3000, "across"), cue(6000, 8000, "end")]);
+    }
+    
+    #[test]
+    fn retimed_cues_that_collapse_are_dropped() {
+        let cues = [cue(1000, 2000, "gone"), cue(2000, 5000, "kept")];
+        
+        // Everything before 2s maps onto the same instant, as inside a transition
+        let retimed = retime_cues(&cues, |ms| ms.saturating_sub(2000), 10_000);
+        assert_eq!(retimed, [cue(0, 3000, "kept")]);
+    }
+    
+    #[test]
+    fn srt_output_numbers_cues() {
+        let srt = to_srt(&[cue(1000, 2500, "Hello"), cue(3_723_456, 3_724_000, "Later")]);
+        
+        assert_eq!(srt, "1\n00:00:01,000 --> 00:00:02,500\nHello\n\n2\n01:02:03,456 --> 01:02:04,000\nLater\n");
+        assert_eq!(parse_subtitles(&srt).unwrap().len(), 2);
+    }
+}
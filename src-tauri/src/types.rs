@@ -40,18 +40,481 @@ pub struct MediaMetadata {
     pub height: u32,
     pub fps: Option<f64>,
     pub size_bytes: Option<u64>,
+    pub has_audio: bool,
+    pub rotation: u32,  // Clockwise degrees needed to show the video upright; width/height are already rotated
 }
 
 // Export request structures
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct ExportClip {
     pub asset_path: String,
     pub in_ms: u64,
     pub out_ms: u64,
+    pub start_ms: u64,  // Timeline position; space before it is exported as a gap
+    #[serde(default = "default_speed")]
+    pub speed: f64,  // Playback rate, 0.25 to 4.0; audio pitch is preserved
+    #[serde(default)]
+    pub reverse: bool,
+    pub freeze_ms: Option<u64>,  // Hold the frame at in_ms this long instead of playing in..out
+    #[serde(default)]
+    pub gain_db: f64,
+    #[serde(default)]
+    pub video_fade_in_ms: u64,
+    #[serde(default)]
+    pub video_fade_out_ms: u64,
+    #[serde(default)]
+    pub audio_fade_in_ms: u64,
+    #[serde(default)]
+    pub audio_fade_out_ms: u64,
+    #[serde(default)]
+    pub declick: bool,  // Short audio fade at both cuts to avoid pops
+    pub crop: Option<CropRect>,
+    #[serde(default)]
+    pub rotation: u32,  // Clockwise, in 90 degree steps; applied after the crop
+    #[serde(default)]
+    pub flip_horizontal: bool,
+    #[serde(default)]
+    pub flip_vertical: bool,
+    pub color: Option<ColorAdjustments>,
+    pub lut_path: Option<String>,  // .cube 3D LUT applied after the color adjustments
 }
 
-#[derive(Debug, Deserialize)]
+// Per-clip color correction; defaults leave the picture unchanged
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ColorAdjustments {
+    pub brightness: f64,  // -1.0 to 1.0
+    pub contrast: f64,    // 0.0 to 4.0
+    pub saturation: f64,  // 0.0 to 3.0
+    pub gamma: f64,       // 0.1 to 10.0
+    pub temperature: Option<f64>,  // White balance in Kelvin (6500 is neutral)
+}
+
+impl Default for ColorAdjustments {
+    fn default() -> Self {
+        Self {
+            brightness: 0.0,
+            contrast: 1.0,
+            saturation: 1.0,
+            gamma: 1.0,
+            temperature: None,
+        }
+    }
+}
+
+// Crop rectangle in source pixels, measured on the upright (auto-rotated) frame
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub struct CropRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+fn default_speed() -> f64 {
+    1.0
+}
+
+impl ExportClip {
+    /// Length of the clip on the exported timeline, after speed changes and freezes
+    pub fn duration_ms(&self) -> u64 {
+        match self.freeze_ms {
+            Some(freeze_ms) => freeze_ms,
+            None => (self.out_ms.saturating_sub(self.in_ms) as f64 / self.speed).round() as u64,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum TransitionKind {
+    Dissolve,
+    FadeBlack,
+    Wipe,
+    Slide,
+}
+
+// Curve names match ffmpeg's acrossfade curves
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum AudioCurve {
+    #[default]
+    Tri,
+    Qsin,
+    Hsin,
+    Esin,
+    Log,
+    Exp,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportTransition {
+    pub after_clip: usize,  // Transition from clips[after_clip] into the next clip
+    pub kind: TransitionKind,
+    pub duration_ms: u64,   // Overlap between the two clips; shortens the export by this much
+    #[serde(default)]
+    pub audio_curve: AudioCurve,
+}
+
+fn default_scale() -> f64 {
+    1.0
+}
+
+fn default_opacity() -> f64 {
+    1.0
+}
+
+// A clip on an overlay track, composited over the main clips at its timeline position
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TrackClip {
+    pub asset_path: String,
+    pub in_ms: u64,
+    pub out_ms: u64,
+    pub start_ms: u64,
+    #[serde(default)]
+    pub x: i32,  // Top-left corner in project pixels
+    #[serde(default)]
+    pub y: i32,
+    #[serde(default = "default_scale")]
+    pub scale: f64,  // Relative to the source frame size
+    #[serde(default = "default_opacity")]
+    pub opacity: f64,  // 0.0 to 1.0
+    #[serde(default)]
+    pub z_order: i32,  // Higher draws on top; ties follow track and clip order
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportVideoTrack {
+    pub clips: Vec<TrackClip>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AudioTrackClip {
+    pub asset_path: String,
+    pub in_ms: u64,
+    pub out_ms: u64,
+    pub start_ms: u64,
+    #[serde(default)]
+    pub gain_db: f64,
+}
+
+fn default_duck_threshold() -> f64 {
+    0.05
+}
+
+fn default_duck_ratio() -> f64 {
+    8.0
+}
+
+fn default_duck_attack_ms() -> f64 {
+    20.0
+}
+
+fn default_duck_release_ms() -> f64 {
+    400.0
+}
+
+// Sidechain compressor settings for a track that ducks under dialogue
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DuckingSettings {
+    #[serde(default = "default_duck_threshold")]
+    pub threshold: f64,  // Dialogue level (linear, 0-1) that starts ducking
+    #[serde(default = "default_duck_ratio")]
+    pub ratio: f64,
+    #[serde(default = "default_duck_attack_ms")]
+    pub attack_ms: f64,
+    #[serde(default = "default_duck_release_ms")]
+    pub release_ms: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportAudioTrack {
+    pub clips: Vec<AudioTrackClip>,
+    #[serde(default)]
+    pub volume_db: f64,
+    #[serde(default)]
+    pub muted: bool,
+    #[serde(default)]
+    pub solo: bool,  // Any solo track silences the main clips and every non-solo track
+    #[serde(default)]
+    pub ducking: Option<DuckingSettings>,  // Lower this track while the main clips or other tracks play
+}
+
+fn default_font_size() -> u32 {
+    48
+}
+
+fn default_text_color() -> String {
+    "white".to_string()
+}
+
+// Title or lower-third drawn over the timeline between start_ms and end_ms
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TextOverlay {
+    pub text: String,
+    pub font_file: String,
+    #[serde(default = "default_font_size")]
+    pub font_size: u32,
+    #[serde(default = "default_text_color")]
+    pub color: String,
+    pub box_color: Option<String>,  // Background box behind the text, e.g. "black@0.6"
+    #[serde(default)]
+    pub box_padding: u32,
+    pub x: i32,  // Top-left corner in project pixels
+    pub y: i32,
+    pub start_ms: u64,
+    pub end_ms: u64,
+    #[serde(default)]
+    pub fade_in_ms: u64,
+    #[serde(default)]
+    pub fade_out_ms: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum Corner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    #[default]
+    BottomRight,
+}
+
+fn default_watermark_margin() -> u32 {
+    20
+}
+
+fn default_watermark_scale() -> f64 {
+    0.15
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Watermark {
+    pub image_path: String,  // PNG, transparency is kept
+    #[serde(default)]
+    pub corner: Corner,
+    #[serde(default = "default_watermark_margin")]
+    pub margin: u32,  // Pixels from the corner's edges
+    #[serde(default = "default_watermark_scale")]
+    pub scale: f64,  // Logo width as a fraction of the output width
+    #[serde(default = "default_opacity")]
+    pub opacity: f64,
+    pub start_ms: Option<u64>,  // Timeline range; unset ends mean the start/end of the export
+    pub end_ms: Option<u64>,
+}
+
+fn default_target_lufs() -> f64 {
+    -16.0
+}
+
+fn default_true_peak_db() -> f64 {
+    -1.5
+}
+
+fn default_lra() -> f64 {
+    11.0
+}
+
+// EBU R128 loudness normalization target, e.g. -16 LUFS for web or -23 LUFS for broadcast
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LoudnessTarget {
+    #[serde(default = "default_target_lufs")]
+    pub integrated_lufs: f64,
+    #[serde(default = "default_true_peak_db")]
+    pub true_peak_db: f64,
+    #[serde(default = "default_lra")]
+    pub lra: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum SubtitleMode {
+    Burn,  // Rendered into the picture
+    Soft,  // Muxed as a selectable subtitle track
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum SubtitlePosition {
+    #[default]
+    Bottom,
+    Middle,
+    Top,
+}
+
+// Styling for burned-in subtitles
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SubtitleStyle {
+    pub font_name: Option<String>,
+    pub font_size: u32,
+    pub outline: f64,
+    pub position: SubtitlePosition,
+}
+
+impl Default for SubtitleStyle {
+    fn default() -> Self {
+        Self {
+            font_name: None,
+            font_size: 24,
+            outline: 2.0,
+            position: SubtitlePosition::Bottom,
+        }
+    }
+}
+
+// SRT or WebVTT file whose cue times are timeline times
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SubtitleTrack {
+    pub path: String,
+    pub mode: SubtitleMode,
+    #[serde(default)]
+    pub style: SubtitleStyle,
+    pub language: Option<String>,  // ISO 639-2 code for soft tracks, e.g. "eng"
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum VideoCodec {
+    #[default]
+    H264,
+    H265,
+}
+
+fn default_target_audio_kbps() -> u32 {
+    128
+}
+
+// Size-capped export: the video bitrate is derived from the size, duration and audio bitrate
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TargetSize {
+    pub size_bytes: u64,
+    #[serde(default)]
+    pub codec: VideoCodec,
+    #[serde(default = "default_target_audio_kbps")]
+    pub audio_bitrate_kbps: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum StreamingFormat {
+    #[default]
+    Hls,
+    Dash,
+}
+
+// One step of the bitrate ladder; the width follows the export's aspect ratio
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct StreamingRendition {
+    pub height: u32,
+    pub video_bitrate_kbps: u32,
+    pub audio_bitrate_kbps: u32,
+}
+
+fn default_segment_duration() -> u32 {
+    6
+}
+
+fn default_renditions() -> Vec<StreamingRendition> {
+    [(1080, 5000, 192), (720, 2800, 128), (480, 1400, 96)]
+        .iter()
+        .map(|&(height, video_bitrate_kbps, audio_bitrate_kbps)| StreamingRendition {
+            height,
+            video_bitrate_kbps,
+            audio_bitrate_kbps,
+        })
+        .collect()
+}
+
+// Segmented output for adaptive streaming; output_path is then a directory holding
+// master.m3u8 (HLS) or manifest.mpd (DASH) and the segments
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct StreamingOutput {
+    #[serde(default)]
+    pub format: StreamingFormat,
+    #[serde(default = "default_segment_duration")]
+    pub segment_duration_s: u32,  // Every segment starts on a keyframe at this interval in all renditions
+    #[serde(default = "default_renditions")]
+    pub renditions: Vec<StreamingRendition>,  // Highest first
+}
+
+// Section of the timeline to export; clips crossing either end are trimmed to it
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub struct TimelineRange {
+    pub start_ms: u64,
+    pub end_ms: u64,
+}
+
+// Still image taken from the finished export at a timeline time
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PosterFrame {
+    pub time_ms: u64,
+    pub path: Option<String>,  // Defaults to "<output name>_poster.jpg" next to the export
+}
+
+fn default_sheet_columns() -> u32 {
+    4
+}
+
+fn default_sheet_rows() -> u32 {
+    4
+}
+
+fn default_thumbnail_width() -> u32 {
+    320
+}
+
+// Grid of evenly spaced frames from the finished export, each labelled with its time
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ContactSheet {
+    #[serde(default = "default_sheet_columns")]
+    pub columns: u32,
+    #[serde(default = "default_sheet_rows")]
+    pub rows: u32,
+    #[serde(default = "default_thumbnail_width")]
+    pub thumbnail_width: u32,
+    pub font_file: Option<String>,  // Label font; fontconfig's default when unset
+    pub path: Option<String>,  // Defaults to "<output name>_contact_sheet.jpg" next to the export
+}
+
+// Container-level tags written into the output file
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct OutputMetadata {
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub comment: Option<String>,
+    pub creation_time: Option<String>,  // ISO 8601, e.g. "2024-05-01T12:30:00Z"
+    pub language: Option<String>,  // ISO 639-2 code for the video and audio streams, e.g. "eng"
+}
+
+// Chapter starting at a timeline time; it runs until the next chapter or the end of the export
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ChapterMarker {
+    pub start_ms: u64,
+    pub title: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 #[allow(dead_code)]
 pub struct ExportRequest {
@@ -59,16 +522,172 @@ pub struct ExportRequest {
     pub output_path: String,  // Passed separately to export_concat, not read from struct
     pub width: Option<u32>,
     pub height: Option<u32>,
+    pub gap_color: Option<String>,  // Fill color for timeline gaps, defaults to black
+    #[serde(default)]
+    pub transitions: Vec<ExportTransition>,
+    #[serde(default)]
+    pub video_tracks: Vec<ExportVideoTrack>,  // Overlay tracks above the main clips (video only)
+    #[serde(default)]
+    pub audio_tracks: Vec<ExportAudioTrack>,  // Mixed with the main clips' embedded audio
+    #[serde(default)]
+    pub text_overlays: Vec<TextOverlay>,
+    pub watermark: Option<Watermark>,
+    pub loudness: Option<LoudnessTarget>,  // Two-pass loudnorm of the final mix
+    #[serde(default)]
+    pub subtitles: Vec<SubtitleTrack>,
+    pub target_size: Option<TargetSize>,  // Two-pass encode to fit a file size cap
+    pub range: Option<TimelineRange>,  // Export only this part of the timeline
+    pub streaming: Option<StreamingOutput>,
+    pub metadata: Option<OutputMetadata>,
+    #[serde(default)]
+    pub chapters: Vec<ChapterMarker>,
+    #[serde(default)]
+    pub chapters_per_clip: bool,  // One chapter per main clip, named after its asset file
+    #[serde(default)]
+    pub web_optimized: bool,  // Faststart MP4 with a closed, fixed GOP for progressive playback
+    pub poster_frame: Option<PosterFrame>,
+    pub contact_sheet: Option<ContactSheet>,
+}
+
+// Timeline formats other editors read
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum InterchangeFormat {
+    Edl,  // CMX3600
+    Fcpxml,
+    Otio,  // OpenTimelineIO JSON
+}
+
+// An imported edit whose media couldn't be found or read
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnresolvedMedia {
+    pub event: usize,  // 1-based position among the file's video edits
+    pub reel: String,
+    pub clip_name: Option<String>,
+    pub source_file: Option<String>,
+    pub start_ms: u64,
+    pub duration_ms: u64,
+    pub reason: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InterchangeImport {
+    pub clips: Vec<ExportClip>,  // Ready for ExportRequest.clips; unresolved edits are left out
+    pub unresolved: Vec<UnresolvedMedia>,
+}
+
+// Encoding settings shared by every file of a batch export
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportProfile {
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub watermark: Option<Watermark>,
+    pub loudness: Option<LoudnessTarget>,
+    pub target_size: Option<TargetSize>,  // Applies to each file on its own
+    #[serde(default)]
+    pub web_optimized: bool,
+    pub metadata: Option<OutputMetadata>,
+}
+
+fn default_filename_template() -> String {
+    "{index}_{asset_name}".to_string()
+}
+
+// Export each clip as its own file in `output_dir`
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClipBatchRequest {
+    pub clips: Vec<ExportClip>,
+    pub output_dir: String,
+    // {index}, {asset_name}, {in} and {out}; ".mp4" is added when the template has no extension
+    #[serde(default = "default_filename_template")]
+    pub filename_template: String,
+    #[serde(default)]
+    pub profile: ExportProfile,
+}
+
+// Overall progress of a batch export; each file's own progress arrives as export-progress events
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ClipBatchProgress {
+    pub index: usize,  // File being exported, 0-based
+    pub count: usize,
+    pub output_path: String,
+    pub progress: f32,  // 0.0 to 1.0, weighted by clip duration
+    pub message: String,
 }
 
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ExportPrepareResult {
+    pub job_id: String,
     pub segment_paths: Vec<String>,
     pub list_file: String,
     pub total_duration_ms: u64,
 }
 
+// Loudness of the export before normalization, as measured by loudnorm's first pass
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LoudnessMeasurement {
+    pub integrated_lufs: f64,
+    pub true_peak_db: f64,
+    pub lra: f64,
+    pub threshold_lufs: f64,
+    pub target_offset_db: f64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportResult {
+    pub output_path: String,
+    pub size_bytes: u64,
+    pub loudness: Option<LoudnessMeasurement>,
+    pub verification: ExportVerification,
+    pub log_path: String,  // Per-job log with the request and every ffmpeg run
+    pub poster_path: Option<String>,
+    pub contact_sheet_path: Option<String>,
+}
+
+// What ffprobe found in the finished file, next to what the export should have produced
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportVerification {
+    pub duration_ms: u64,
+    pub expected_duration_ms: u64,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub expected_width: Option<u32>,  // Unknown when export_concat is called without a job
+    pub expected_height: Option<u32>,
+    pub has_video: bool,
+    pub has_audio: bool,
+    pub subtitle_streams: usize,
+    pub playlists: Vec<String>,  // Streaming manifests and playlists that were read, relative to the output
+}
+
+// Export jobs handed from export_prepare to export_concat
+pub type ExportJobs = Arc<Mutex<HashMap<String, ExportJob>>>;
+
+#[derive(Debug)]
+pub struct ExportJob {
+    pub request: ExportRequest,
+    pub segments: Vec<PreparedSegment>,
+    pub width: u32,   // Frame size and rate every segment is conformed to
+    pub height: u32,
+    pub fps: f64,
+    pub created: std::time::Instant,  // Jobs nobody concatenates are dropped after STALE_JOB_AGE
+}
+
+#[derive(Debug)]
+pub struct PreparedSegment {
+    pub path: String,
+    pub duration_ms: u64,
+    pub transition_in: Option<ExportTransition>,  // Transition from the previous segment
+}
+
 #[derive(Debug, Serialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct ExportProgress {
@@ -92,6 +711,13 @@ pub struct FFprobeStream {
     pub width: Option<u32>,
     pub height: Option<u32>,
     pub r_frame_rate: Option<String>,
+    pub tags: Option<HashMap<String, String>>,
+    pub side_data_list: Option<Vec<FFprobeSideData>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FFprobeSideData {
+    pub rotation: Option<f64>,  // Display matrix rotation, counter-clockwise degrees
 }
 
 #[derive(Debug, Deserialize)]
@@ -22,6 +22,7 @@ interface ExportClip {
   assetPath: string;
   inMs: number;
   outMs: number;
+  startMs: number;
 }
 
 interface ExportRequest {
@@ -29,9 +30,11 @@ interface ExportRequest {
   outputPath: string;
   width?: number;
   height?: number;
+  gapColor?: string;
 }
 
 interface ExportPrepareResult {
+  jobId: string;
   segmentPaths: string[];
   listFile: string;
   totalDurationMs: number;
@@ -100,6 +103,7 @@ export function ExportDialog({ clips, onClose }: ExportDialogProps) {
         assetPath: clip.assetPath,
         inMs: clip.inMs,
         outMs: clip.outMs,
+        startMs: clip.startMs,
       }));
 
       const request: ExportRequest = {
@@ -146,6 +150,7 @@ export function ExportDialog({ clips, onClose }: ExportDialogProps) {
         listFile: prepareResult.listFile,
         outputPath,
         totalDurationMs: prepareResult.totalDurationMs,
+        jobId: prepareResult.jobId,
       });
 
       // Success - progress listener will handle completion
@@ -168,8 +173,9 @@ export function ExportDialog({ clips, onClose }: ExportDialogProps) {
     return `${minutes}:${seconds.toString().padStart(2, "0")}`;
   };
 
-  const totalDuration = clips.reduce((sum, clip) => {
-    return sum + (clip.outMs - clip.inMs);
+  // Gaps are exported too, so the duration runs to the end of the last clip
+  const totalDuration = clips.reduce((end, clip) => {
+    return Math.max(end, clip.startMs + (clip.outMs - clip.inMs));
   }, 0);
 
   return (
@@ -25,6 +25,8 @@ export type MediaMetadata = {
   height: number;
   fps?: number;
   sizeBytes?: number;
+  hasAudio: boolean;
+  rotation: number; // clockwise degrees; width/height are already the upright size
 };
 
 // Timeline types